@@ -0,0 +1,48 @@
+//! Compares both solvers of part 2 of day 5 on the input: resolving every seed against mapping
+//! whole intervals of seeds. Run with `cargo bench --bench day5`, the input being read from
+//! `AOC_INPUT_DIR` like when solving
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use aoc2k23::Planting;
+
+/// How many times the solver by intervals runs, its timing being the average of the runs
+const INTERVALS_RUNS: u32 = 1000;
+
+/// Runs `solve` `runs` times, returning its answer along with the average time it took
+fn bench<T>(runs: u32, solve: impl Fn() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    for _ in 1..runs {
+        black_box(solve());
+    }
+    let answer = solve();
+    (answer, start.elapsed() / runs)
+}
+
+fn main() -> anyhow::Result<()> {
+    let path = aoc2k23::input_path(5);
+    let planting = std::fs::read_to_string(&path)
+        .map_err(|e| anyhow::anyhow!("failed to read {}: {e}", path.display()))?
+        .parse::<Planting>()?;
+
+    // Every seed is resolved, a single run is long enough to be measured
+    let (per_seed, per_seed_time) = bench(1, || planting.lowest_location_per_seed());
+    let (by_intervals, by_intervals_time) =
+        bench(INTERVALS_RUNS, || planting.lowest_location_by_intervals());
+
+    println!("per seed:     {per_seed:?} in {per_seed_time:?}");
+    println!("by intervals: {by_intervals:?} in {by_intervals_time:?}");
+    println!(
+        "speedup: {:.0}x",
+        per_seed_time.as_secs_f64() / by_intervals_time.as_secs_f64()
+    );
+
+    if per_seed != by_intervals {
+        anyhow::bail!("the solvers disagree: {per_seed:?} against {by_intervals:?}");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,254 @@
+//! Answers of the days stored on disk, keyed by the hash of their input
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail};
+
+/// The answers of both parts of a day, those that were solved
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct Answers {
+    pub part1: Option<String>,
+    pub part2: Option<String>,
+}
+
+impl Answers {
+    fn to_json(&self) -> String {
+        let value = |answer: &Option<String>| match answer {
+            Some(answer) => quote(answer),
+            None => "null".to_string(),
+        };
+
+        format!(
+            "{{\"part1\":{},\"part2\":{}}}",
+            value(&self.part1),
+            value(&self.part2)
+        )
+    }
+
+    fn from_json(s: &str) -> anyhow::Result<Self> {
+        let mut rest = s
+            .trim()
+            .strip_prefix('{')
+            .and_then(|s| s.strip_suffix('}'))
+            .ok_or(anyhow!("expected an object"))?
+            .trim_start();
+
+        let mut answers = Self::default();
+        while !rest.is_empty() {
+            let (key, remainder) = unquote(rest)?;
+            let remainder = remainder
+                .trim_start()
+                .strip_prefix(':')
+                .ok_or(anyhow!("missing `:` after `{key}`"))?
+                .trim_start();
+
+            let (value, remainder) = match remainder.strip_prefix("null") {
+                Some(remainder) => (None, remainder),
+                None => unquote(remainder).map(|(value, remainder)| (Some(value), remainder))?,
+            };
+
+            match key.as_str() {
+                "part1" => answers.part1 = value,
+                "part2" => answers.part2 = value,
+                _ => bail!("unknown key `{key}`"),
+            }
+
+            let remainder = remainder.trim_start();
+            rest = match remainder.strip_prefix(',') {
+                Some(remainder) => remainder.trim_start(),
+                None if remainder.is_empty() => remainder,
+                None => bail!("expected `,` after `{key}`"),
+            };
+        }
+
+        Ok(answers)
+    }
+}
+
+pub(super) fn quote(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c < '\u{20}' => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Parses the string at the start of `s`, returning it along with the rest of `s`
+fn unquote(s: &str) -> anyhow::Result<(String, &str)> {
+    let s = s.strip_prefix('"').ok_or(anyhow!("expected a string"))?;
+
+    let mut unquoted = String::new();
+    let mut chars = s.char_indices();
+    while let Some((idx, c)) = chars.next() {
+        match c {
+            '"' => return Ok((unquoted, &s[idx + 1..])),
+            '\\' => match chars.next() {
+                Some((_, 'n')) => unquoted.push('\n'),
+                Some((_, 'r')) => unquoted.push('\r'),
+                Some((_, 't')) => unquoted.push('\t'),
+                Some((_, 'u')) => {
+                    let hex = s
+                        .get(idx + 2..idx + 6)
+                        .ok_or(anyhow!("truncated unicode escape"))?;
+                    let c = u32::from_str_radix(hex, 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .ok_or(anyhow!("invalid unicode escape `{hex}`"))?;
+                    unquoted.push(c);
+                    chars.nth(3);
+                }
+                Some((_, c @ ('"' | '\\'))) => unquoted.push(c),
+                _ => bail!("invalid escape sequence"),
+            },
+            c => unquoted.push(c),
+        }
+    }
+
+    bail!("unterminated string")
+}
+
+/// The 64 bits FNV-1a hash of `bytes`
+fn fnv1a(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    bytes.iter().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(PRIME)
+    })
+}
+
+/// The cached answers of a day for a given input. Changing the input, the version of the crate or
+/// the revision of the solver of the day changes the hash, and thus the entry, which is how stale
+/// answers get invalidated
+#[derive(Debug)]
+pub(super) struct Entry {
+    path: PathBuf,
+}
+
+impl Entry {
+    pub(super) fn new(dir: &Path, day: usize, revision: u32, input: &Path) -> anyhow::Result<Self> {
+        let mut key = format!("{}:{revision}:", env!("CARGO_PKG_VERSION")).into_bytes();
+        key.extend(fs::read(input)?);
+        let hash = fnv1a(&key);
+        Ok(Self {
+            path: dir.join(format!("day{day}-{hash:016x}.json")),
+        })
+    }
+
+    /// The cached answers, if any. An unreadable entry is treated as missing
+    pub(super) fn load(&self) -> Option<Answers> {
+        let content = fs::read_to_string(&self.path).ok()?;
+        Answers::from_json(&content).ok()
+    }
+
+    pub(super) fn store(&self, answers: &Answers) -> anyhow::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        fs::write(&self.path, answers.to_json())
+            .map_err(|e| anyhow!("failed to write {}: {e}", self.path.display()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn answers_json_round_trip() -> anyhow::Result<()> {
+        let answers = Answers {
+            part1: Some("142".to_string()),
+            part2: None,
+        };
+        assert_eq!(answers.to_json(), r#"{"part1":"142","part2":null}"#);
+        assert_eq!(Answers::from_json(&answers.to_json())?, answers);
+
+        let answers = Answers {
+            part1: Some("a \"quoted\" \\ answer".to_string()),
+            part2: Some("two\nlines".to_string()),
+        };
+        assert_eq!(Answers::from_json(&answers.to_json())?, answers);
+
+        let answers = Answers {
+            part1: Some("\t\r\u{0}\u{1b}[32m\u{1f}".to_string()),
+            part2: Some("é ✓".to_string()),
+        };
+        assert_eq!(
+            answers.to_json(),
+            r#"{"part1":"\t\r\u0000\u001b[32m\u001f","part2":"é ✓"}"#
+        );
+        assert_eq!(Answers::from_json(&answers.to_json())?, answers);
+        assert_eq!(
+            Answers::from_json(r#"{"part1":"\u00e9\u0041"}"#)?
+                .part1
+                .as_deref(),
+            Some("éA")
+        );
+
+        assert_eq!(
+            Answers::from_json(r#" { "part2" : "5905" } "#)?,
+            Answers {
+                part1: None,
+                part2: Some("5905".to_string())
+            }
+        );
+        assert_eq!(Answers::from_json("{}")?, Answers::default());
+
+        Ok(())
+    }
+
+    #[test]
+    fn corrupted_answers() {
+        for json in [
+            "",
+            "{",
+            r#"{"part1":}"#,
+            r#"{"part1":"142""#,
+            r#"{"part1":"142" "part2":null}"#,
+            r#"{"part3":"1"}"#,
+            r#"{"part1":"\u00"}"#,
+            r#"{"part1":"\u00zz"}"#,
+            r#"{"part1":"\ud800"}"#,
+            r#"{"part1":"\x"}"#,
+        ] {
+            assert!(Answers::from_json(json).is_err(), "{json}");
+        }
+    }
+
+    #[test]
+    fn entries_change_with_the_revision() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join(format!("aoc2k23-entry-{}", std::process::id()));
+        let input = dir.join("input.txt");
+        fs::create_dir_all(&dir)?;
+        fs::write(&input, "a\nb\n")?;
+
+        let entry = |day, revision| Entry::new(&dir, day, revision, &input).map(|e| e.path);
+        assert_eq!(entry(5, 0)?, entry(5, 0)?);
+        assert_ne!(entry(5, 0)?, entry(5, 1)?);
+        assert_ne!(entry(5, 0)?, entry(6, 0)?);
+
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn fnv1a_known_values() {
+        assert_eq!(fnv1a(b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a(b"a"), 0xaf63dc4c8601ec8c);
+        assert_ne!(fnv1a(b"ab"), fnv1a(b"ba"));
+    }
+}
@@ -0,0 +1,327 @@
+//! Parsing of the command line
+
+use std::{path::PathBuf, str::FromStr};
+
+use anyhow::{anyhow, bail};
+
+use crate::day::{Options, Part};
+
+/// Where the answers are cached, unless running with `--no-cache`
+const CACHE_DIR: &str = ".aoc_cache";
+
+/// Parses a part, `1`, `2` or `all`
+fn parse_part(part: &str) -> anyhow::Result<Part> {
+    match part.trim() {
+        "1" => Ok(Part::One),
+        "2" => Ok(Part::Two),
+        "all" => Ok(Part::All),
+        part => bail!("invalid part `{part}`"),
+    }
+}
+
+/// Parses a day of the advent, between 1 and 25
+fn parse_day(day: &str) -> anyhow::Result<usize> {
+    let day = day
+        .trim()
+        .parse::<usize>()
+        .map_err(|e| anyhow!("invalid day `{day}`: {e}"))?;
+    if !(1..=25).contains(&day) {
+        bail!("day {day} is not between 1 and 25");
+    }
+    Ok(day)
+}
+
+/// The days to run, each one with the parts to solve, or `None` for the default parts of the day
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(super) struct RunSpec(pub(super) Vec<(usize, Option<Part>)>);
+
+/// A comma separated list of days or ranges of days, each one optionally followed by `:` and the
+/// part to solve, like `1-4:1,5:2,6`
+impl FromStr for RunSpec {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut runs: Vec<(usize, Option<Part>)> = Vec::new();
+
+        for item in s.split(',') {
+            let (days, part) = match item.split_once(':') {
+                Some((days, part)) => (days, Some(part)),
+                None => (item, None),
+            };
+
+            let part = part
+                .map(|part| parse_part(part).map_err(|e| anyhow!("{e} in `{item}`")))
+                .transpose()?;
+            let day = |day: &str| parse_day(day).map_err(|e| anyhow!("{e} in `{item}`"));
+
+            let (first, last) = match days.split_once('-') {
+                Some((first, last)) => (day(first)?, day(last)?),
+                None => (day(days)?, day(days)?),
+            };
+            if first > last {
+                bail!("empty range of days `{days}`");
+            }
+
+            for day in first..=last {
+                if runs.iter().any(|(d, _)| *d == day) {
+                    bail!("day {day} is listed more than once");
+                }
+                runs.push((day, part));
+            }
+        }
+
+        Ok(Self(runs))
+    }
+}
+
+/// Where and how the reports are written
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub(super) enum Output {
+    /// Human readable reports on stdout
+    #[default]
+    Text,
+    /// One JSON object per report on stdout
+    Json,
+    /// The same JSON lines, sent to a TCP socket at the given address
+    Tcp(String),
+}
+
+impl FromStr for Output {
+    type Err = anyhow::Error;
+
+    /// Parses the destination of `--emit`, like `tcp://127.0.0.1:9000`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_prefix("tcp://") {
+            Some(addr) if !addr.is_empty() => Ok(Self::Tcp(addr.to_string())),
+            Some(_) => bail!("missing address in `{s}`"),
+            None => bail!("unsupported destination `{s}`, expected `tcp://host:port`"),
+        }
+    }
+}
+
+/// Everything that can be given on the command line
+#[derive(Debug, Clone)]
+pub(super) struct Args {
+    pub(super) options: Options,
+    /// The days to run instead of every solved day
+    pub(super) run: Option<RunSpec>,
+    /// The input of the single day given by `--day`, instead of its default input. `-` reads the
+    /// standard input
+    pub(super) input: Option<PathBuf>,
+    pub(super) output: Output,
+}
+
+/// Parses the command line arguments, without the name of the program
+pub(super) fn parse_args(args: impl IntoIterator<Item = String>) -> anyhow::Result<Args> {
+    let mut options = Options {
+        cache: Some(PathBuf::from(CACHE_DIR)),
+        ..Options::default()
+    };
+    let mut run = None;
+    let mut output = Output::default();
+    let (mut day, mut part, mut input) = (None, None, None);
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--explain" => options.explain = true,
+            "--no-cache" => options.cache = None,
+            "--progress" => options.progress = true,
+            "--tie-wins" => options.tie_wins = true,
+            "--strict-ties" => options.strict_ties = true,
+            "--lenient-cards" => options.lenient_cards = true,
+            "--warmup" => {
+                let count = args.next().ok_or(anyhow!("missing value for `--warmup`"))?;
+                options.warmup = count
+                    .parse()
+                    .map_err(|e| anyhow!("invalid warmup `{count}`: {e}"))?;
+            }
+            "--seeds" => {
+                let path = args.next().ok_or(anyhow!("missing value for `--seeds`"))?;
+                options.seeds = Some(PathBuf::from(path));
+            }
+            "--max-cubes" => {
+                let count = args
+                    .next()
+                    .ok_or(anyhow!("missing value for `--max-cubes`"))?;
+                options.max_cubes = Some(
+                    count
+                        .parse()
+                        .map_err(|e| anyhow!("invalid maximum count `{count}`: {e}"))?,
+                );
+            }
+            "--precision" => {
+                let digits = args
+                    .next()
+                    .ok_or(anyhow!("missing value for `--precision`"))?;
+                options.precision = digits
+                    .parse()
+                    .map_err(|e| anyhow!("invalid precision `{digits}`: {e}"))?;
+            }
+            "--json" => output = Output::Json,
+            "--emit" => {
+                let destination = args.next().ok_or(anyhow!("missing value for `--emit`"))?;
+                output = destination.parse()?;
+            }
+            "--day" => {
+                let value = args.next().ok_or(anyhow!("missing value for `--day`"))?;
+                day = Some(parse_day(&value)?);
+            }
+            "--part" => {
+                let value = args.next().ok_or(anyhow!("missing value for `--part`"))?;
+                part = Some(parse_part(&value)?);
+            }
+            "--input" => {
+                let path = args.next().ok_or(anyhow!("missing value for `--input`"))?;
+                input = Some(PathBuf::from(path));
+            }
+            "--run" => {
+                let spec = args.next().ok_or(anyhow!("missing value for `--run`"))?;
+                run = Some(spec.parse()?);
+            }
+            _ => bail!("unknown argument `{arg}`"),
+        }
+    }
+
+    match day {
+        Some(_) if run.is_some() => bail!("`--day` and `--run` can't be combined"),
+        Some(day) => run = Some(RunSpec(vec![(day, part)])),
+        None if part.is_some() => bail!("`--part` needs `--day`"),
+        None if input.is_some() => bail!("`--input` needs `--day`"),
+        None => {}
+    }
+
+    Ok(Args {
+        options,
+        run,
+        input,
+        output,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    fn options(args: &[&str]) -> anyhow::Result<Options> {
+        Ok(parse_args(self::args(args))?.options)
+    }
+
+    #[test]
+    fn parse_options_flags() -> anyhow::Result<()> {
+        let parsed = options(&["--warmup", "3", "--explain"])?;
+        assert_eq!(parsed.warmup, 3);
+        assert!(parsed.explain);
+
+        assert_eq!(options(&[])?.warmup, 0);
+        assert_eq!(options(&[])?.precision, 3);
+        assert_eq!(options(&[])?.cache, Some(PathBuf::from(CACHE_DIR)));
+        assert_eq!(options(&["--no-cache"])?.cache, None);
+        assert!(options(&["--progress"])?.progress);
+        assert!(!options(&[])?.progress);
+        assert_eq!(options(&["--precision", "5"])?.precision, 5);
+        assert_eq!(
+            options(&["--seeds", "seeds.txt"])?.seeds,
+            Some(PathBuf::from("seeds.txt"))
+        );
+        assert_eq!(options(&[])?.max_cubes, None);
+        assert!(options(&["--tie-wins"])?.tie_wins);
+        assert!(!options(&[])?.tie_wins);
+        assert!(options(&["--strict-ties"])?.strict_ties);
+        assert!(!options(&[])?.strict_ties);
+        assert!(options(&["--lenient-cards"])?.lenient_cards);
+        assert!(!options(&[])?.lenient_cards);
+        assert_eq!(options(&["--max-cubes", "20"])?.max_cubes, Some(20));
+        assert!(options(&["--max-cubes", "-1"]).is_err());
+        assert!(options(&["--warmup"]).is_err());
+        assert!(options(&["--warmup", "many"]).is_err());
+        assert!(options(&["--fast"]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_run_specs() -> anyhow::Result<()> {
+        assert_eq!(
+            "1-4:1,5:2".parse::<RunSpec>()?,
+            RunSpec(vec![
+                (1, Some(Part::One)),
+                (2, Some(Part::One)),
+                (3, Some(Part::One)),
+                (4, Some(Part::One)),
+                (5, Some(Part::Two)),
+            ])
+        );
+        assert_eq!(
+            "7,2:all, 3".parse::<RunSpec>()?,
+            RunSpec(vec![(7, None), (2, Some(Part::All)), (3, None)])
+        );
+        assert_eq!("6-6".parse::<RunSpec>()?, RunSpec(vec![(6, None)]));
+
+        assert_eq!(
+            parse_args(args(&["--run", "5:2"]))?.run,
+            Some(RunSpec(vec![(5, Some(Part::Two))]))
+        );
+        assert_eq!(parse_args(args(&[]))?.run, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_single_day() -> anyhow::Result<()> {
+        let parsed = parse_args(args(&["--day", "5", "--part", "2", "--input", "path.txt"]))?;
+        assert_eq!(parsed.run, Some(RunSpec(vec![(5, Some(Part::Two))])));
+        assert_eq!(parsed.input, Some(PathBuf::from("path.txt")));
+
+        let parsed = parse_args(args(&["--day", "7"]))?;
+        assert_eq!(parsed.run, Some(RunSpec(vec![(7, None)])));
+        assert_eq!(parsed.input, None);
+
+        for args in [
+            &["--part", "2"][..],
+            &["--input", "path.txt"],
+            &["--day", "5", "--run", "6"],
+            &["--day", "26"],
+            &["--day", "5", "--part", "3"],
+            &["--day"],
+        ] {
+            assert!(parse_args(self::args(args)).is_err(), "{args:?}");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_outputs() -> anyhow::Result<()> {
+        assert_eq!(parse_args(args(&[]))?.output, Output::Text);
+        assert_eq!(parse_args(args(&["--json"]))?.output, Output::Json);
+        assert_eq!(
+            parse_args(args(&["--emit", "tcp://127.0.0.1:9000"]))?.output,
+            Output::Tcp("127.0.0.1:9000".to_string())
+        );
+
+        for destination in ["127.0.0.1:9000", "tcp://", "udp://127.0.0.1:9000"] {
+            assert!(
+                parse_args(args(&["--emit", destination])).is_err(),
+                "{destination}"
+            );
+        }
+        assert!(parse_args(args(&["--emit"])).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn reject_malformed_run_specs() {
+        for spec in [
+            "", "1,", "0", "26", "a", "4-1", "1-", "-3", "1:3", "1:", "1:2:2", "1-3,2",
+        ] {
+            assert!(spec.parse::<RunSpec>().is_err(), "{spec}");
+        }
+        assert!(parse_args(args(&["--run"])).is_err());
+    }
+}
@@ -1,13 +1,83 @@
 use std::{
-    fmt::{Debug, Display},
-    io::{self, BufRead},
-    path::Path,
+    fmt::{self, Debug, Display},
+    io::{self, BufRead, Read, Write},
+    path::{Path, PathBuf},
     str::FromStr,
+    time::{Duration, Instant},
 };
 
 use anyhow::{anyhow, bail};
 
-#[allow(dead_code)]
+use crate::{cache, util};
+
+#[derive(Debug, Clone)]
+pub(super) struct Options {
+    /// Print the narration of the solution of the day, when it provides one
+    pub(super) explain: bool,
+
+    /// How many times each part is solved, discarding the result, before the measured run
+    pub(super) warmup: usize,
+
+    /// The significant digits of the timings
+    pub(super) precision: usize,
+
+    /// A file whose line of numbers replaces the seeds of the almanac of day 5
+    pub(super) seeds: Option<PathBuf>,
+
+    /// The directory in which the answers are cached, when caching
+    pub(super) cache: Option<PathBuf>,
+
+    /// Print how many lines were parsed while parsing the input
+    pub(super) progress: bool,
+
+    /// The highest count of cubes a withdraw of day 2 may have, any count being accepted otherwise
+    pub(super) max_cubes: Option<u64>,
+
+    /// Tying the record of a race of day 6 is enough to win it
+    pub(super) tie_wins: bool,
+
+    /// Reject the hands of day 7 that tie, instead of ranking them in their input order
+    pub(super) strict_ties: bool,
+
+    /// Accept `1` for the ten cards of the hands of day 7
+    pub(super) lenient_cards: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            explain: false,
+            warmup: 0,
+            precision: DEFAULT_PRECISION,
+            seeds: None,
+            cache: None,
+            progress: false,
+            max_cubes: None,
+            tie_wins: false,
+            strict_ties: false,
+            lenient_cards: false,
+        }
+    }
+}
+
+impl Options {
+    /// Whether the answers solved with these options can be cached and read back. The options of
+    /// the days change their answers, or reject their input, without changing the input, while
+    /// explaining and warming up are only meaningful when actually solving
+    fn cacheable(&self) -> bool {
+        self.seeds.is_none()
+            && self.max_cubes.is_none()
+            && !self.tie_wins
+            && !self.strict_ties
+            && !self.lenient_cards
+            && !self.explain
+            && self.warmup == 0
+    }
+}
+
+const DEFAULT_PRECISION: usize = 3;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub(super) enum Part {
     All,
     One,
@@ -18,74 +88,1189 @@ pub trait Day {
     type Item: FromStr + Clone;
     type Answer: Display;
 
+    /// What both parts are solved from, built once from the items
+    type Context: TryFrom<Vec<Self::Item>, Error: Into<anyhow::Error>>;
+
     const DAY: usize;
 
-    fn part_1(_items: Vec<Self::Item>) -> anyhow::Result<Self::Answer> {
-        bail!("unsolved yet")
+    /// Whether the items of this day can be parsed while the input is being read, without holding
+    /// all of its lines in memory first
+    const STREAMING: bool = false;
+
+    /// Whether the items of this day are parsed from the whole input at once by `parse_input`,
+    /// instead of one per line
+    const WHOLE_FILE: bool = false;
+
+    /// The unit of the answers, printed after them
+    const UNIT: &'static str = "";
+
+    /// Bumped whenever a fix of the solver changes its answers, so that those cached before are
+    /// not reused
+    const REVISION: u32 = 0;
+
+    /// Parses the items from the whole input, one per line unless overridden
+    fn parse_input(input: &str) -> anyhow::Result<Vec<Self::Item>>
+    where
+        <Self::Item as FromStr>::Err: Display,
+    {
+        parse_lines(lines(input.as_bytes()), |_| {})
+    }
+
+    /// Builds the context of both parts, through its conversion from the items unless overridden
+    fn build_context(items: Vec<Self::Item>) -> anyhow::Result<Self::Context> {
+        Self::Context::try_from(items).map_err(Into::into)
+    }
+
+    fn part_1(_context: &Self::Context) -> anyhow::Result<Self::Answer> {
+        Err(Unsolved.into())
+    }
+
+    fn part_2(_context: &Self::Context) -> anyhow::Result<Self::Answer> {
+        Err(Unsolved.into())
+    }
+
+    /// Adjusts the parsed items to the options before solving, for the options specific to the day
+    fn with_options(items: Vec<Self::Item>, _options: &Options) -> anyhow::Result<Vec<Self::Item>> {
+        Ok(items)
     }
 
-    fn part_2(_items: Vec<Self::Item>) -> anyhow::Result<Self::Answer> {
-        bail!("unsolved yet")
+    /// Checks the parsed items as a whole before solving, to reject an input that can't be right
+    fn validate_items(_items: &[Self::Item], _options: &Options) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// A human explanation of what the solution computes for the given items
+    fn explain(_items: &[Self::Item]) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// A relation that must hold between the answers of both parts, checked when solving all parts
+    fn check_invariant(_part_1: &Self::Answer, _part_2: &Self::Answer) -> bool {
+        true
+    }
+}
+
+/// The error of a part that has no solution yet, told apart from the parts failing to solve
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub(super) struct Unsolved;
+
+impl Display for Unsolved {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("unsolved yet")
     }
 }
 
+impl std::error::Error for Unsolved {}
+
+/// The byte order mark some editors write at the start of UTF-8 files
+const BOM: char = '\u{feff}';
+
+/// The lines of `reader`, without the byte order mark it may start with
+fn lines(reader: impl BufRead) -> impl Iterator<Item = anyhow::Result<String>> {
+    reader.lines().enumerate().map(|(idx, line)| {
+        let mut line = line?;
+        if idx == 0 && line.starts_with(BOM) {
+            line.remove(0);
+        }
+        Ok(line)
+    })
+}
+
+/// The bytes gzipped files start with
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Opens `path`, decompressing it when it is gzipped, which is told by its `.gz` extension or by its
+/// first bytes
+fn open(path: &Path) -> anyhow::Result<Box<dyn BufRead>> {
+    let mut reader = io::BufReader::new(std::fs::File::open(path)?);
+    let gzipped = path.extension().is_some_and(|ext| ext == "gz")
+        || reader.fill_buf()?.starts_with(&GZIP_MAGIC);
+    if !gzipped {
+        return Ok(Box::new(reader));
+    }
+
+    gunzip(reader, path)
+}
+
+#[cfg(feature = "flate2")]
+fn gunzip(reader: impl BufRead + 'static, _path: &Path) -> anyhow::Result<Box<dyn BufRead>> {
+    Ok(Box::new(io::BufReader::new(
+        flate2::bufread::MultiGzDecoder::new(reader),
+    )))
+}
+
+#[cfg(not(feature = "flate2"))]
+fn gunzip(_reader: impl BufRead + 'static, path: &Path) -> anyhow::Result<Box<dyn BufRead>> {
+    Err(anyhow!(
+        "{} is gzipped, which needs the `flate2` feature",
+        path.display()
+    ))
+}
+
+fn stream_lines(
+    path: impl AsRef<Path>,
+) -> anyhow::Result<impl Iterator<Item = anyhow::Result<String>>> {
+    Ok(lines(open(path.as_ref())?))
+}
+
 fn read_lines(path: impl AsRef<Path>) -> anyhow::Result<Vec<String>> {
-    let file = std::fs::File::open(path)?;
+    stream_lines(path)?.collect()
+}
+
+/// The whole content of `reader`, without the byte order mark it may start with
+fn read_all(mut reader: impl Read) -> anyhow::Result<String> {
+    let mut content = String::new();
+    reader.read_to_string(&mut content)?;
+    if content.starts_with(BOM) {
+        content.remove(0);
+    }
+
+    Ok(content)
+}
+
+/// Reads the whole content of `path` at once, decompressing it when it is gzipped
+fn read_content(path: &Path) -> anyhow::Result<String> {
+    read_all(open(path)?)
+}
+
+/// The path standing for the standard input
+pub(super) const STDIN: &str = "-";
+
+/// Reads every line of the standard input, given as `reader`, which must not be empty
+fn read_stdin(reader: impl BufRead) -> anyhow::Result<Vec<String>> {
+    let lines = lines(reader).collect::<anyhow::Result<Vec<_>>>()?;
+    if lines.is_empty() {
+        bail!("no input on stdin");
+    }
+
+    Ok(lines)
+}
+
+/// Reads the whole standard input, given as `reader`, which must not be empty
+fn read_stdin_content(reader: impl Read) -> anyhow::Result<String> {
+    let content = read_all(reader)?;
+    if content.is_empty() {
+        bail!("no input on stdin");
+    }
+
+    Ok(content)
+}
+
+/// How often the parsing progress is printed
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Wraps `report` so that it is called at most once every `interval`
+fn throttled(interval: Duration, mut report: impl FnMut(usize)) -> impl FnMut(usize) {
+    let mut last = Instant::now();
+    move |parsed| {
+        if last.elapsed() >= interval {
+            report(parsed);
+            last = Instant::now();
+        }
+    }
+}
+
+/// Writes the number of parsed lines to `out`
+fn print_progress(mut out: impl Write) -> impl FnMut(usize) {
+    move |parsed| {
+        // Failing to report the progress is not worth failing the parsing
+        let _ = writeln!(out, "parsed {parsed} lines");
+    }
+}
+
+/// Parses every line, calling `progress` with the number of lines parsed so far after each one
+fn parse_lines<T: FromStr>(
+    lines: impl Iterator<Item = anyhow::Result<String>>,
+    mut progress: impl FnMut(usize),
+) -> anyhow::Result<Vec<T>>
+where
+    T::Err: Display,
+{
+    lines
+        .enumerate()
+        .map(|(idx, line)| {
+            let item = line?.parse().map_err(|e| anyhow!("line {}: {e}", idx + 1));
+            progress(idx + 1);
+            item
+        })
+        .collect()
+}
 
-    let reader = io::BufReader::new(file);
-    let mut ret = Vec::new();
-    for line in reader.lines() {
-        ret.push(line?)
+/// The number of lines from which parsing them in parallel pays off
+const PARALLEL_PARSE_LINES: usize = 100_000;
+
+/// Same as `parse_lines`, parsing chunks of lines in parallel on `threads` threads
+fn parse_lines_parallel<T: FromStr + Send>(
+    lines: &[String],
+    threads: usize,
+) -> anyhow::Result<Vec<T>>
+where
+    T::Err: Display,
+{
+    let chunk_len = lines.len().div_ceil(threads.max(1)).max(1);
+
+    std::thread::scope(|s| {
+        let handles = lines
+            .chunks(chunk_len)
+            .enumerate()
+            .map(|(chunk, lines)| {
+                s.spawn(move || {
+                    let first = chunk * chunk_len;
+                    lines
+                        .iter()
+                        .enumerate()
+                        .map(|(idx, line)| {
+                            line.parse()
+                                .map_err(|e| anyhow!("line {}: {e}", first + idx + 1))
+                        })
+                        .collect::<anyhow::Result<Vec<T>>>()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // Joining in order keeps the items in order, and reports the error of the first bad line
+        let mut items = Vec::with_capacity(lines.len());
+        for handle in handles {
+            items.extend(handle.join().expect("parsing thread panicked")?);
+        }
+        Ok(items)
+    })
+}
+
+/// The outcome of solving a day, computed without printing anything
+#[derive(Debug, Clone)]
+pub(super) struct DayReport {
+    pub(super) day: usize,
+    pub(super) part: Part,
+    pub(super) input: String,
+    pub(super) unit: &'static str,
+    pub(super) explanation: Vec<String>,
+    pub(super) part1: Option<String>,
+    pub(super) part2: Option<String>,
+    /// How long reading and parsing the input took
+    pub(super) parse_time: Option<Duration>,
+    pub(super) part1_time: Option<Duration>,
+    pub(super) part2_time: Option<Duration>,
+    pub(super) errors: Vec<String>,
+    pub(super) warnings: Vec<String>,
+    /// The requested parts that have no solution yet
+    pub(super) unsolved: Vec<usize>,
+    /// Whether the answers come from the cache instead of being solved
+    pub(super) cached: bool,
+}
+
+impl DayReport {
+    fn new<D: Day>(input: &str, part: Part) -> Self {
+        Self {
+            day: D::DAY,
+            part,
+            input: input.to_string(),
+            unit: D::UNIT,
+            explanation: Vec::new(),
+            part1: None,
+            part2: None,
+            parse_time: None,
+            part1_time: None,
+            part2_time: None,
+            errors: Vec::new(),
+            warnings: Vec::new(),
+            unsolved: Vec::new(),
+            cached: false,
+        }
     }
 
-    Ok(ret)
+    /// A report of the `cached` answers, when they contain every requested part
+    fn from_cache<D: Day>(input: &str, part: Part, cached: &cache::Answers) -> Option<Self> {
+        let mut report = Self::new::<D>(input, part);
+        if matches!(part, Part::All | Part::One) {
+            report.part1 = Some(cached.part1.clone()?);
+        }
+        if matches!(part, Part::All | Part::Two) {
+            report.part2 = Some(cached.part2.clone()?);
+        }
+
+        report.cached = true;
+        Some(report)
+    }
+
+    /// The report as a single line JSON object, for tools consuming the answers
+    pub(super) fn to_json(&self) -> String {
+        let answer = |answer: &Option<String>| match answer {
+            Some(answer) => cache::quote(answer),
+            None => "null".to_string(),
+        };
+        let errors = self
+            .errors
+            .iter()
+            .map(|e| cache::quote(e))
+            .collect::<Vec<_>>();
+
+        format!(
+            "{{\"day\":{},\"part1\":{},\"part2\":{},\"errors\":[{}],\"cached\":{}}}",
+            self.day,
+            answer(&self.part1),
+            answer(&self.part2),
+            errors.join(","),
+            self.cached
+        )
+    }
+
+    fn with_unit(&self, answer: &str) -> String {
+        match self.unit {
+            "" => answer.to_string(),
+            unit => format!("{answer} {unit}"),
+        }
+    }
+}
+
+/// The precision of the formatter, when given, is the number of significant digits of the timings
+impl Display for DayReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (day, input) = (self.day, &self.input);
+        let cached = if self.cached { " (cached)" } else { "" };
+        match self.part {
+            Part::One => writeln!(f, "Solving day {day} (part 1) [{input}]{cached}")?,
+            Part::Two => writeln!(f, "Solving day {day} (part 2) [{input}]{cached}")?,
+            Part::All => writeln!(f, "Solving day {day} [{input}]{cached}")?,
+        }
+
+        let precision = f.precision().unwrap_or(DEFAULT_PRECISION);
+        if let Some(time) = self.parse_time {
+            writeln!(f, "Parsed in {}", util::format_duration(time, precision))?;
+        }
+
+        for line in &self.explanation {
+            writeln!(f, "{line}")?;
+        }
+
+        let mut errors = self.errors.iter().collect::<Vec<_>>();
+        let answers = [
+            (1, &self.part1, self.part1_time),
+            (2, &self.part2, self.part2_time),
+        ];
+        for (part, answer, time) in answers {
+            if let Some(answer) = answer {
+                let answer = self.with_unit(answer);
+                match self.part {
+                    Part::All => write!(f, "Answer for part {part}: {answer}")?,
+                    Part::One | Part::Two => write!(f, "Answer {answer}")?,
+                }
+                match time {
+                    Some(time) => {
+                        writeln!(f, " (took {})", util::format_duration(time, precision))?
+                    }
+                    None => writeln!(f)?,
+                }
+            }
+
+            if self.unsolved.contains(&part) {
+                writeln!(f, "part {part} is not implemented")?;
+            }
+
+            let prefix = format!("part {part}:");
+            for error in errors.iter().filter(|e| e.starts_with(&prefix)) {
+                writeln!(f, "failed to solve {error}")?;
+            }
+            errors.retain(|e| !e.starts_with(&prefix));
+        }
+
+        for error in errors {
+            writeln!(f, "failed to solve {error}")?;
+        }
+
+        for warning in &self.warnings {
+            writeln!(f, "warning: {warning}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The sum of the numeric answers of several days
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub(super) struct Total {
+    pub(super) sum: u64,
+    pub(super) solved: usize,
+}
+
+impl Total {
+    /// Sums the answers of `reports` that are numbers, skipping the others
+    pub(super) fn of(reports: &[DayReport]) -> Self {
+        reports
+            .iter()
+            .flat_map(|report| [&report.part1, &report.part2])
+            .flatten()
+            .filter_map(|answer| answer.parse::<u64>().ok())
+            .fold(Self::default(), |total, answer| Self {
+                sum: total.sum + answer,
+                solved: total.solved + 1,
+            })
+    }
 }
 
-pub(super) fn solve<D: Day>(file: impl AsRef<Path>, part: Part) -> anyhow::Result<()>
+impl Display for Total {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Total of {} solved parts: {}", self.solved, self.sum)
+    }
+}
+
+/// The parts of several days that have no solution yet, as `(day, part)` pairs
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub(super) struct UnsolvedParts(pub(super) Vec<(usize, usize)>);
+
+impl UnsolvedParts {
+    pub(super) fn of(reports: &[DayReport]) -> Self {
+        Self(
+            reports
+                .iter()
+                .flat_map(|report| report.unsolved.iter().map(|part| (report.day, *part)))
+                .collect(),
+        )
+    }
+}
+
+/// Prints nothing when every part is solved
+impl Display for UnsolvedParts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return Ok(());
+        }
+
+        let parts = self
+            .0
+            .iter()
+            .map(|(day, part)| format!("day {day} part {part}"))
+            .collect::<Vec<_>>();
+        writeln!(f, "Unsolved: {}", parts.join(", "))
+    }
+}
+
+/// Reads the items of the day from `file` and solves the requested parts
+pub(super) fn report<D: Day>(file: &Path, part: Part, options: &Options) -> DayReport
 where
+    D::Item: Send,
     <<D as Day>::Item as FromStr>::Err: Debug + Display,
 {
-    let day = D::DAY;
-
-    let file = file.as_ref();
-    let file_path = file
-        .to_str()
-        .ok_or(anyhow!("failed to determine fail path"))?;
-
-    let items = read_lines(file)?
-        .into_iter()
-        .map(|l| l.parse())
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| anyhow!("{e}"))?;
-
-    match part {
-        Part::One => {
-            println!("Solving day {day} (part 1) [{file_path}]");
-            match D::part_1(items) {
-                Ok(answer) => println!("Answer {answer}"),
-                Err(e) => println!("failed to solve: {e}"),
-            };
-        }
-        Part::Two => {
-            println!("Solving day {day} (part 2) [{file_path}]");
-            match D::part_2(items) {
-                Ok(answer) => println!("Answer {answer}"),
-                Err(e) => println!("failed to solve: {e}"),
-            };
-        }
-        Part::All => {
-            println!("Solving day {day} [{file_path}]");
-
-            match D::part_1(items.clone()) {
-                Ok(answer) => println!("Answer for part 1: {answer}"),
-                Err(e) => println!("failed to solve part 1: {e}"),
-            };
-
-            match D::part_2(items.clone()) {
-                Ok(answer) => println!("Answer for part 2: {answer}"),
-                Err(e) => println!("failed to solve part 2: {e}"),
-            };
+    let stdin = file == Path::new(STDIN);
+    let input = if stdin {
+        "<stdin>".to_string()
+    } else {
+        file.display().to_string()
+    };
+
+    let entry = match &options.cache {
+        // The standard input can only be read once, so it is never hashed
+        Some(dir) if !stdin && options.cacheable() => {
+            cache::Entry::new(dir, D::DAY, D::REVISION, file).ok()
+        }
+        _ => None,
+    };
+    let cached = entry
+        .as_ref()
+        .and_then(cache::Entry::load)
+        .unwrap_or_default();
+    if let Some(report) = DayReport::from_cache::<D>(&input, part, &cached) {
+        return report;
+    }
+
+    let mut progress = options
+        .progress
+        .then(|| throttled(PROGRESS_INTERVAL, print_progress(io::stderr())));
+    let progress = |parsed| {
+        if let Some(progress) = &mut progress {
+            progress(parsed);
+        }
+    };
+
+    let start = Instant::now();
+    let items = if D::WHOLE_FILE {
+        let content = if stdin {
+            read_stdin_content(io::stdin().lock())
+        } else {
+            read_content(file)
+        };
+        content.and_then(|content| D::parse_input(&content))
+    } else if stdin {
+        read_stdin(io::stdin().lock())
+            .and_then(|lines| parse_lines(lines.into_iter().map(Ok), progress))
+    } else if D::STREAMING {
+        stream_lines(file).and_then(|lines| parse_lines(lines, progress))
+    } else {
+        read_lines(file).and_then(|lines| {
+            // Progress is only followed when parsing sequentially
+            if lines.len() >= PARALLEL_PARSE_LINES
+                && !options.progress
+                && cfg!(not(target_arch = "wasm32"))
+            {
+                let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+                parse_lines_parallel(&lines, threads)
+            } else {
+                parse_lines(lines.into_iter().map(Ok), progress)
+            }
+        })
+    };
+
+    let parse_time = start.elapsed();
+
+    let mut report = match items {
+        Ok(items) => DayReport {
+            parse_time: Some(parse_time),
+            ..run::<D>(&input, items, part, options)
+        },
+        Err(e) => {
+            let mut report = DayReport::new::<D>(&input, part);
+            report.errors.push(format!("day {}: {e}", D::DAY));
+            report
+        }
+    };
+
+    if let Some(entry) = entry.filter(|_| report.errors.is_empty()) {
+        let answers = cache::Answers {
+            part1: report.part1.clone().or(cached.part1),
+            part2: report.part2.clone().or(cached.part2),
+        };
+        if let Err(e) = entry.store(&answers) {
+            report
+                .warnings
+                .push(format!("failed to cache the answers: {e}"));
+        }
+    }
+
+    report
+}
+
+/// Solves the requested parts of `input`, without reading files nor measuring time, neither of
+/// which is available when running in a browser
+pub(super) fn solve_str<D: Day>(input: &str, part: Part) -> anyhow::Result<cache::Answers>
+where
+    <<D as Day>::Item as FromStr>::Err: Display,
+{
+    let items = D::parse_input(input).map_err(|e| anyhow!("day {}: {e}", D::DAY))?;
+    D::validate_items(&items, &Options::default()).map_err(|e| anyhow!("day {}: {e}", D::DAY))?;
+    let context = D::build_context(items).map_err(|e| anyhow!("day {}: {e}", D::DAY))?;
+
+    let part1 = matches!(part, Part::All | Part::One)
+        .then(|| D::part_1(&context).map_err(|e| anyhow!("part 1: {e}")))
+        .transpose()?;
+    let part2 = matches!(part, Part::All | Part::Two)
+        .then(|| D::part_2(&context).map_err(|e| anyhow!("part 2: {e}")))
+        .transpose()?;
+
+    Ok(cache::Answers {
+        part1: part1.map(|answer| answer.to_string()),
+        part2: part2.map(|answer| answer.to_string()),
+    })
+}
+
+/// Solves a single part from the `context` built for it, after `warmup` discarded runs, returning
+/// its answer along with how long solving it took, without printing anything
+pub(super) fn timed_run<D: Day>(
+    context: &D::Context,
+    part: Part,
+    warmup: usize,
+) -> anyhow::Result<(D::Answer, Duration)> {
+    let (answer, time) = match part {
+        Part::One => timed(warmup, || D::part_1(context)),
+        Part::Two => timed(warmup, || D::part_2(context)),
+        Part::All => bail!("only a single part can be timed"),
+    };
+    let answer = answer.map_err(|e| match part {
+        Part::One => e.context("part 1"),
+        _ => e.context("part 2"),
+    })?;
+    Ok((answer, time))
+}
+
+/// Calls `solve` `warmup` times, then once more while measuring how long it takes
+fn timed<T>(warmup: usize, solve: impl Fn() -> T) -> (T, Duration) {
+    for _ in 0..warmup {
+        solve();
+    }
+
+    let start = Instant::now();
+    let result = solve();
+    (result, start.elapsed())
+}
+
+fn run<D: Day>(input: &str, items: Vec<D::Item>, part: Part, options: &Options) -> DayReport {
+    let mut report = DayReport::new::<D>(input, part);
+
+    let items = match D::with_options(items, options) {
+        Ok(items) => items,
+        Err(e) => {
+            report.errors.push(format!("day {}: {e}", D::DAY));
+            return report;
+        }
+    };
+
+    if let Err(e) = D::validate_items(&items, options) {
+        report.errors.push(format!("day {}: {e}", D::DAY));
+        return report;
+    }
+
+    if options.explain {
+        report.explanation = D::explain(&items);
+    }
+
+    let context = match D::build_context(items) {
+        Ok(context) => context,
+        Err(e) => {
+            report.errors.push(format!("day {}: {e}", D::DAY));
+            return report;
         }
     };
-    Ok(())
+
+    let solve = |solved: Part| {
+        (part == Part::All || part == solved)
+            .then(|| timed_run::<D>(&context, solved, options.warmup))
+    };
+    let part_1 = solve(Part::One);
+    let part_2 = solve(Part::Two);
+
+    if let Some(Ok((answer, time))) = &part_1 {
+        report.part1 = Some(answer.to_string());
+        report.part1_time = Some(*time);
+    }
+
+    if let Some(Ok((answer, time))) = &part_2 {
+        report.part2 = Some(answer.to_string());
+        report.part2_time = Some(*time);
+    }
+
+    for (n, solved) in [(1, &part_1), (2, &part_2)] {
+        match solved {
+            Some(Err(e)) if e.is::<Unsolved>() => report.unsolved.push(n),
+            Some(Err(e)) => report.errors.push(format!("{e:#}")),
+            _ => {}
+        }
+    }
+
+    if let (Some(Ok((part_1, _))), Some(Ok((part_2, _)))) = (&part_1, &part_2) {
+        if !D::check_invariant(part_1, part_2) {
+            report.warnings.push(format!(
+                "answers {part_1} and {part_2} do not hold the invariant of day {}",
+                D::DAY
+            ));
+        }
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{self, AtomicUsize};
+
+    use super::*;
+
+    #[test]
+    fn timed_run_of_a_single_part() -> anyhow::Result<()> {
+        use crate::day1::Day1;
+
+        let context = Day1::build_context(read_lines("src/day1/example.txt")?)?;
+        let (answer, time) = timed_run::<Day1>(&context, Part::Two, 2)?;
+        assert_eq!(answer, 281);
+        assert!(time > Duration::ZERO);
+
+        assert!(timed_run::<Day1>(&context, Part::All, 0).is_err());
+
+        let error = timed_run::<Failing>(&Vec::new(), Part::One, 0).unwrap_err();
+        assert_eq!(format!("{error:#}"), "part 1: no winning hold");
+
+        Ok(())
+    }
+
+    #[test]
+    fn streaming_day_is_reported() {
+        use crate::day2::Day2;
+
+        // Day 2 parses its games while its lines are read
+        let streamed = report::<Day2>(
+            Path::new("src/day2/example.txt"),
+            Part::All,
+            &Options::default(),
+        );
+        assert_eq!(streamed.part1.as_deref(), Some("8"));
+        assert_eq!(streamed.part2.as_deref(), Some("2286"));
+        assert!(streamed.errors.is_empty());
+
+        let missing = report::<Day2>(
+            Path::new("src/day2/missing.txt"),
+            Part::All,
+            &Options::default(),
+        );
+        assert_eq!(missing.errors.len(), 1);
+    }
+
+    #[test]
+    fn stream_lines_matches_read_lines() -> anyhow::Result<()> {
+        let path = "src/day7/example.txt";
+        let streamed = stream_lines(path)?.collect::<anyhow::Result<Vec<_>>>()?;
+        assert_eq!(streamed, read_lines(path)?);
+        assert_eq!(streamed.len(), 5);
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_progress_is_throttled() -> anyhow::Result<()> {
+        let input = crate::testing::gen_input(7, 100_000)?;
+        let lines = || input.lines().map(|line| Ok(line.to_string()));
+
+        let mut sink = Vec::new();
+        let items = parse_lines::<String>(lines(), print_progress(&mut sink))?;
+        assert_eq!(items.len(), 100_000);
+
+        let printed = String::from_utf8(sink)?;
+        assert_eq!(printed.lines().count(), 100_000);
+        assert_eq!(printed.lines().last(), Some("parsed 100000 lines"));
+
+        let mut sink = Vec::new();
+        parse_lines::<String>(
+            lines(),
+            throttled(Duration::from_secs(3600), print_progress(&mut sink)),
+        )?;
+        assert!(sink.is_empty());
+
+        let mut sink = Vec::new();
+        parse_lines::<String>(
+            lines(),
+            throttled(Duration::ZERO, print_progress(&mut sink)),
+        )?;
+        assert!(String::from_utf8(sink)?.starts_with("parsed 1 lines\nparsed 2 lines\n"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn gzipped_input() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join(format!("aoc2k23-gzip-{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+
+        let plain = Path::new("src/day1/example_part1.txt");
+        let gzipped = dir.join("input.txt.gz");
+        #[cfg(feature = "flate2")]
+        {
+            let mut encoder =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(&std::fs::read(plain)?)?;
+            std::fs::write(&gzipped, encoder.finish()?)?;
+        }
+        #[cfg(not(feature = "flate2"))]
+        std::fs::write(&gzipped, GZIP_MAGIC)?;
+
+        let options = Options::default();
+        let expected = report::<crate::day1::Day1>(plain, Part::One, &options);
+        let report = report::<crate::day1::Day1>(&gzipped, Part::One, &options);
+        std::fs::remove_dir_all(&dir)?;
+
+        assert_eq!(expected.part1.as_deref(), Some("142"));
+        if cfg!(feature = "flate2") {
+            assert_eq!(report.part1, expected.part1);
+        } else {
+            assert!(report.errors[0].contains("needs the `flate2` feature"));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn empty_stdin_is_an_error() -> anyhow::Result<()> {
+        let err = read_stdin(io::empty()).unwrap_err();
+        assert_eq!(err.to_string(), "no input on stdin");
+
+        assert_eq!(read_stdin(b"\xef\xbb\xbfa\nb\n".as_slice())?, ["a", "b"]);
+        assert_eq!(read_stdin(b"\n".as_slice())?, [""]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn byte_order_mark_is_stripped() -> anyhow::Result<()> {
+        let mut input = vec![0xef, 0xbb, 0xbf];
+        input.extend_from_slice(b"Game 1: 3 blue, 4 red\nGame 2: 1 blue");
+
+        let lines = lines(input.as_slice()).collect::<anyhow::Result<Vec<_>>>()?;
+        assert_eq!(lines, ["Game 1: 3 blue, 4 red", "Game 2: 1 blue"]);
+
+        let games = parse_lines::<crate::day2::Game>(lines.into_iter().map(Ok), |_| {})?;
+        assert_eq!(games.len(), 2);
+
+        // Only a leading mark is stripped
+        let input = "a\n\u{feff}b";
+        let lines = super::lines(input.as_bytes()).collect::<anyhow::Result<Vec<_>>>()?;
+        assert_eq!(lines, ["a", "\u{feff}b"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn parallel_parsing_matches_sequential() -> anyhow::Result<()> {
+        type Hand = crate::day7::Hand<crate::day7::Part1>;
+
+        let input = crate::testing::gen_input(7, 50_000)?;
+        let mut lines = input.lines().map(str::to_string).collect::<Vec<_>>();
+
+        let sequential = parse_lines::<Hand>(lines.iter().cloned().map(Ok), |_| {})?;
+        for threads in [1, 3, 8, 64] {
+            assert_eq!(parse_lines_parallel::<Hand>(&lines, threads)?, sequential);
+        }
+
+        lines[37_123] = "AAAAA".to_string();
+        lines[45_000] = "AAAA 1".to_string();
+        let sequential = parse_lines::<Hand>(lines.iter().cloned().map(Ok), |_| {}).unwrap_err();
+        assert_eq!(sequential.to_string(), "line 37124: invalid hand");
+        for threads in [1, 3, 8] {
+            let parallel = parse_lines_parallel::<Hand>(&lines, threads).unwrap_err();
+            assert_eq!(parallel.to_string(), sequential.to_string());
+        }
+
+        Ok(())
+    }
+
+    struct Increasing<const PART_1: u32, const PART_2: u32>;
+    impl<const PART_1: u32, const PART_2: u32> Day for Increasing<PART_1, PART_2> {
+        type Item = String;
+        type Answer = u32;
+        type Context = Vec<Self::Item>;
+
+        const DAY: usize = 0;
+
+        fn part_1(_context: &Self::Context) -> anyhow::Result<Self::Answer> {
+            Ok(PART_1)
+        }
+
+        fn part_2(_context: &Self::Context) -> anyhow::Result<Self::Answer> {
+            Ok(PART_2)
+        }
+
+        fn check_invariant(part_1: &Self::Answer, part_2: &Self::Answer) -> bool {
+            part_2 >= part_1
+        }
+    }
+
+    fn output<D: Day>(part: Part) -> anyhow::Result<String> {
+        Ok(run::<D>("test", Vec::new(), part, &Options::default()).to_string())
+    }
+
+    struct Ways;
+    impl Day for Ways {
+        type Item = String;
+        type Answer = u32;
+        type Context = Vec<Self::Item>;
+
+        const DAY: usize = 0;
+        const UNIT: &'static str = "ways";
+
+        fn part_1(_context: &Self::Context) -> anyhow::Result<Self::Answer> {
+            Ok(288)
+        }
+    }
+
+    #[test]
+    fn unsolved_parts_are_collected() {
+        let options = Options::default();
+        let reports = [
+            run::<Increasing<5, 10>>("test", Vec::new(), Part::All, &options),
+            run::<Ways>("test", Vec::new(), Part::All, &options),
+            run::<Ways>("test", Vec::new(), Part::One, &options),
+        ];
+        assert_eq!(reports[1].unsolved, [2]);
+
+        let unsolved = UnsolvedParts::of(&reports);
+        assert_eq!(unsolved, UnsolvedParts(vec![(0, 2)]));
+        assert_eq!(unsolved.to_string(), "Unsolved: day 0 part 2\n");
+        assert_eq!(UnsolvedParts::of(&reports[..1]).to_string(), "");
+    }
+
+    struct Failing;
+    impl Day for Failing {
+        type Item = String;
+        type Answer = u32;
+        type Context = Vec<Self::Item>;
+
+        const DAY: usize = 0;
+
+        fn part_1(_context: &Self::Context) -> anyhow::Result<Self::Answer> {
+            anyhow::bail!("no winning hold")
+        }
+    }
+
+    #[test]
+    fn unsolved_parts_are_not_failures() -> anyhow::Result<()> {
+        let printed = output::<Ways>(Part::All)?;
+        assert!(printed.contains("part 2 is not implemented"), "{printed}");
+        assert!(!printed.contains("failed"), "{printed}");
+
+        let printed = output::<Failing>(Part::All)?;
+        assert!(
+            printed.contains("failed to solve part 1: no winning hold"),
+            "{printed}"
+        );
+        assert!(printed.contains("part 2 is not implemented"), "{printed}");
+
+        let report = run::<Failing>("test", Vec::new(), Part::All, &Options::default());
+        assert_eq!(report.errors, ["part 1: no winning hold"]);
+        assert_eq!(report.unsolved, [2]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn report_as_json() {
+        let report = run::<Ways>("test", Vec::new(), Part::One, &Options::default());
+        assert_eq!(
+            report.to_json(),
+            r#"{"day":0,"part1":"288","part2":null,"errors":[],"cached":false}"#
+        );
+
+        let report = run::<Failing>("test", Vec::new(), Part::All, &Options::default());
+        assert_eq!(
+            report.to_json(),
+            r#"{"day":0,"part1":null,"part2":null,"errors":["part 1: no winning hold"],"cached":false}"#
+        );
+    }
+
+    #[test]
+    fn parse_time_is_printed() -> anyhow::Result<()> {
+        let report = report::<Ways>(
+            Path::new("src/day6/example.txt"),
+            Part::One,
+            &Options::default(),
+        );
+        assert!(report.parse_time.is_some());
+
+        let printed = report.to_string();
+        let lines = printed.lines().collect::<Vec<_>>();
+        assert!(lines[1].starts_with("Parsed in "), "{printed}");
+        assert!(lines[2].starts_with("Answer 288 ways (took "), "{printed}");
+
+        // Nothing is parsed when the answers are only solved
+        assert!(!output::<Ways>(Part::One)?.contains("Parsed in"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn unit_is_printed() -> anyhow::Result<()> {
+        assert!(output::<Ways>(Part::All)?.contains("Answer for part 1: 288 ways (took "));
+        assert!(output::<Ways>(Part::One)?.contains("Answer 288 ways (took "));
+        assert!(output::<Increasing<5, 10>>(Part::One)?.contains("Answer 5 (took "));
+
+        Ok(())
+    }
+
+    static SLOW_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    struct Slow;
+    impl Day for Slow {
+        type Item = String;
+        type Answer = u32;
+        type Context = Vec<Self::Item>;
+
+        const DAY: usize = 0;
+
+        fn part_1(_context: &Self::Context) -> anyhow::Result<Self::Answer> {
+            SLOW_CALLS.fetch_add(1, atomic::Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(20));
+            Ok(1)
+        }
+    }
+
+    #[test]
+    fn warmup_is_not_timed() {
+        let options = Options {
+            warmup: 4,
+            ..Options::default()
+        };
+        let report = run::<Slow>("test", Vec::new(), Part::One, &options);
+
+        assert_eq!(SLOW_CALLS.load(atomic::Ordering::SeqCst), 5);
+        assert_eq!(report.part1.as_deref(), Some("1"));
+
+        let time = report.part1_time.expect("part 1 is timed");
+        assert!(time >= Duration::from_millis(20));
+
+        // Only the call after the warmup is timed: its answer is the one returned, and its timing
+        // fits between the start of the last warmup call and the return of `timed`
+        let calls = std::cell::RefCell::new(Vec::new());
+        let (answer, time) = timed(4, || {
+            calls.borrow_mut().push(Instant::now());
+            let count = calls.borrow().len();
+            if count <= 4 {
+                std::thread::sleep(Duration::from_millis(5));
+            }
+            count
+        });
+        let returned = Instant::now();
+        let calls = calls.into_inner();
+
+        assert_eq!(answer, 5);
+        assert_eq!(calls.len(), 5);
+        assert!(time <= returned.duration_since(calls[3]), "took {time:?}");
+    }
+
+    struct Words;
+    impl Day for Words {
+        type Item = String;
+        type Answer = &'static str;
+        type Context = Vec<Self::Item>;
+
+        const DAY: usize = 0;
+
+        fn part_1(_context: &Self::Context) -> anyhow::Result<Self::Answer> {
+            Ok("forty-two")
+        }
+    }
+
+    #[test]
+    fn total_sums_numeric_answers() {
+        let options = Options::default();
+        let reports = [
+            run::<Increasing<5, 10>>("test", Vec::new(), Part::All, &options),
+            run::<Ways>("test", Vec::new(), Part::All, &options),
+            run::<Words>("test", Vec::new(), Part::All, &options),
+        ];
+
+        assert_eq!(
+            Total::of(&reports),
+            Total {
+                sum: 5 + 10 + 288,
+                solved: 3
+            }
+        );
+        assert_eq!(Total::of(&[]), Total::default());
+    }
+
+    #[test]
+    fn timing_precision() {
+        let mut report = run::<Ways>("test", Vec::new(), Part::One, &Options::default());
+        report.part1_time = Some(Duration::from_nanos(1_234_567));
+
+        assert!(report
+            .to_string()
+            .contains("Answer 288 ways (took 1.23ms)\n"));
+        assert!(format!("{report:.5}").contains("Answer 288 ways (took 1.2346ms)\n"));
+    }
+
+    static LINES_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    struct Lines;
+    impl Day for Lines {
+        type Item = String;
+        type Answer = usize;
+        type Context = Vec<Self::Item>;
+
+        const DAY: usize = 0;
+
+        fn part_1(lines: &Self::Context) -> anyhow::Result<Self::Answer> {
+            LINES_CALLS.fetch_add(1, atomic::Ordering::SeqCst);
+            Ok(lines.len())
+        }
+
+        fn part_2(lines: &Self::Context) -> anyhow::Result<Self::Answer> {
+            Ok(lines.len() * 2)
+        }
+    }
+
+    #[test]
+    fn answers_are_cached_by_input() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join(format!("aoc2k23-cache-{}", std::process::id()));
+        let input = dir.join("input.txt");
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(&input, "a\nb\n")?;
+
+        let options = Options {
+            cache: Some(dir.join("cache")),
+            ..Options::default()
+        };
+        let calls = || LINES_CALLS.load(atomic::Ordering::SeqCst);
+
+        let first = report::<Lines>(&input, Part::One, &options);
+        assert!(!first.cached);
+        assert_eq!(first.part1.as_deref(), Some("2"));
+        assert_eq!(calls(), 1);
+
+        let second = report::<Lines>(&input, Part::One, &options);
+        assert!(second.cached);
+        assert_eq!(second.part1.as_deref(), Some("2"));
+        assert!(second.to_string().contains("(cached)"));
+        assert_eq!(calls(), 1);
+
+        // Part 2 was never solved, so it is not in the cache yet
+        let all = report::<Lines>(&input, Part::All, &options);
+        assert!(!all.cached);
+        assert_eq!(calls(), 2);
+        assert!(report::<Lines>(&input, Part::Two, &options).cached);
+
+        std::fs::write(&input, "a\nb\nc\n")?;
+        let changed = report::<Lines>(&input, Part::One, &options);
+        assert!(!changed.cached);
+        assert_eq!(changed.part1.as_deref(), Some("3"));
+        assert_eq!(calls(), 3);
+
+        let validated = Options {
+            max_cubes: Some(1),
+            ..options.clone()
+        };
+        assert!(!report::<Lines>(&input, Part::One, &validated).cached);
+        assert_eq!(calls(), 4);
+
+        let uncached = Options {
+            cache: None,
+            ..options
+        };
+        assert!(!report::<Lines>(&input, Part::One, &uncached).cached);
+        assert_eq!(calls(), 5);
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    static CONTEXT_BUILDS: AtomicUsize = AtomicUsize::new(0);
+
+    struct Shared;
+    impl Day for Shared {
+        type Item = String;
+        type Answer = usize;
+        type Context = Vec<Self::Item>;
+
+        const DAY: usize = 0;
+
+        fn build_context(items: Vec<Self::Item>) -> anyhow::Result<Self::Context> {
+            CONTEXT_BUILDS.fetch_add(1, atomic::Ordering::SeqCst);
+            Ok(items)
+        }
+
+        fn part_1(lines: &Self::Context) -> anyhow::Result<Self::Answer> {
+            Ok(lines.len())
+        }
+
+        fn part_2(lines: &Self::Context) -> anyhow::Result<Self::Answer> {
+            Ok(lines.len() + 1)
+        }
+    }
+
+    #[test]
+    fn context_is_built_once() {
+        let options = Options {
+            warmup: 2,
+            ..Options::default()
+        };
+        let items = vec!["a".to_string(), "b".to_string()];
+        let report = run::<Shared>("test", items, Part::All, &options);
+
+        assert_eq!(CONTEXT_BUILDS.load(atomic::Ordering::SeqCst), 1);
+        assert_eq!(report.part1.as_deref(), Some("2"));
+        assert_eq!(report.part2.as_deref(), Some("3"));
+    }
+
+    #[test]
+    fn invariant_violation_is_reported() -> anyhow::Result<()> {
+        assert!(output::<Increasing<10, 5>>(Part::All)?.contains("warning"));
+        assert!(!output::<Increasing<5, 10>>(Part::All)?.contains("warning"));
+        assert!(!output::<Increasing<10, 5>>(Part::One)?.contains("warning"));
+
+        Ok(())
+    }
 }
@@ -1,6 +1,6 @@
 use anyhow::anyhow;
 
-const DIGIT_RULES: &'static [(&'static str, u32)] = &[
+const DIGIT_RULES: &[(&str, u32)] = &[
     ("one", 1),
     ("two", 2),
     ("three", 3),
@@ -23,8 +23,9 @@ impl Digits for Part1 {
     }
 }
 
-struct Part2;
-impl Digits for Part2 {
+/// Digits and spelled digits. When `REVERSED`, digits spelled backwards, like `eno`, are also found
+struct Part2<const REVERSED: bool = false>;
+impl<const REVERSED: bool> Digits for Part2<REVERSED> {
     fn find(s: &str) -> Vec<u32> {
         let mut digits = Vec::new();
 
@@ -32,6 +33,13 @@ impl Digits for Part2 {
             for idx in s.match_indices(rule.0) {
                 digits.push((idx.0, rule.1));
             }
+
+            if REVERSED {
+                let reversed = rule.0.chars().rev().collect::<String>();
+                for idx in s.match_indices(&reversed) {
+                    digits.push((idx.0, rule.1));
+                }
+            }
         }
 
         for (idx, c) in s.chars().enumerate() {
@@ -40,7 +48,7 @@ impl Digits for Part2 {
             }
         }
 
-        digits.sort_by(|a, b| a.0.cmp(&b.0));
+        digits.sort_by_key(|d| d.0);
         digits.into_iter().map(|d| d.1).collect()
     }
 }
@@ -49,41 +57,85 @@ fn combine_digits(first: u32, second: u32) -> u32 {
     first * 10 + second
 }
 
-fn solve<D: Digits>(lines: Vec<String>) -> anyhow::Result<u32> {
-    let mut sum = 0;
-
-    for line in lines {
-        let digits = D::find(&line);
-
-        let first_digit = digits
-            .first()
-            .copied()
-            .ok_or(anyhow!("missing first digit"))?;
-        let last_digit = digits
-            .last()
-            .copied()
-            .ok_or(anyhow!("missing last digit"))?;
-
-        let combined = combine_digits(first_digit, last_digit);
-
-        sum += combined;
-    }
+/// The calibration value of every line, made of its first and last digits
+fn calibration_values<D: Digits>(lines: &[String]) -> anyhow::Result<Vec<u32>> {
+    lines
+        .iter()
+        .map(|line| {
+            let digits = D::find(line);
+
+            let first_digit = digits
+                .first()
+                .copied()
+                .ok_or(anyhow!("missing first digit"))?;
+            let last_digit = digits
+                .last()
+                .copied()
+                .ok_or(anyhow!("missing last digit"))?;
+
+            Ok(combine_digits(first_digit, last_digit))
+        })
+        .collect()
+}
 
-    Ok(sum)
+fn solve<D: Digits>(lines: &[String]) -> anyhow::Result<u32> {
+    Ok(calibration_values::<D>(lines)?.into_iter().sum())
 }
 
 pub(super) struct Day1;
 impl super::day::Day for Day1 {
     type Item = String;
     type Answer = u32;
+    type Context = Vec<Self::Item>;
 
     const DAY: usize = 1;
 
-    fn part_1(items: Vec<Self::Item>) -> anyhow::Result<Self::Answer> {
-        solve::<Part1>(items)
+    fn part_1(lines: &Self::Context) -> anyhow::Result<Self::Answer> {
+        solve::<Part1>(lines)
     }
 
-    fn part_2(items: Vec<Self::Item>) -> anyhow::Result<Self::Answer> {
-        solve::<Part2>(items)
+    fn part_2(lines: &Self::Context) -> anyhow::Result<Self::Answer> {
+        solve::<Part2>(lines)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::Rng;
+
+    #[test]
+    fn sample_calibration_values() -> anyhow::Result<()> {
+        let lines = std::fs::read_to_string("src/day1/example_part1.txt")?
+            .lines()
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(calibration_values::<Part1>(&lines)?, [12, 38, 15, 77]);
+        assert_eq!(solve::<Part1>(&lines)?, 142);
+
+        assert!(calibration_values::<Part1>(&["abc".to_string()]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn reversed_spellings() {
+        assert_eq!(Part2::<true>::find("enoowt"), [1, 2]);
+        assert_eq!(Part2::<true>::find("thgie3one"), [8, 3, 1]);
+        assert_eq!(Part2::<false>::find("enoowt"), []);
+        assert_eq!(<Part2>::find("enoowt"), []);
+        assert_eq!(Part2::<true>::find("two1nine"), <Part2>::find("two1nine"));
+    }
+
+    #[test]
+    fn strategies_agree_on_digit_only_lines() {
+        let mut rng = Rng::new(1);
+        for _ in 0..5_000 {
+            let len = rng.range(0, 30);
+            let line = (0..len)
+                .map(|_| char::from_digit(rng.range(0, 10) as u32, 10).unwrap())
+                .collect::<String>();
+            assert_eq!(Part1::find(&line), <Part2>::find(&line), "{line}");
+        }
     }
 }
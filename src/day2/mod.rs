@@ -1,7 +1,9 @@
-use std::str::FromStr;
+use std::{fmt, str::FromStr};
 
 use anyhow::{anyhow, bail};
 
+use crate::day::Options;
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 enum Color {
     Red,
@@ -9,6 +11,17 @@ enum Color {
     Blue,
 }
 
+impl Color {
+    /// The index of the color in arrays holding a value per color
+    fn index(self) -> usize {
+        match self {
+            Color::Red => 0,
+            Color::Green => 1,
+            Color::Blue => 2,
+        }
+    }
+}
+
 impl FromStr for Color {
     type Err = anyhow::Error;
 
@@ -22,6 +35,16 @@ impl FromStr for Color {
     }
 }
 
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Color::Red => "red",
+            Color::Green => "green",
+            Color::Blue => "blue",
+        })
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 struct Withdraw {
     count: u64,
@@ -41,16 +64,32 @@ impl FromStr for Withdraw {
         let color = parts
             .next()
             .ok_or(anyhow!("missing count"))
-            .and_then(|c| c.parse().map_err(Into::into))?;
+            .and_then(|c| c.parse())?;
 
         Ok(Self { count, color })
     }
 }
 
+impl fmt::Display for Withdraw {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.count, self.color)
+    }
+}
+
 impl Withdraw {
     fn is_possible(&self, bag: &Bag) -> bool {
         bag.count_for(self.color) >= self.count
     }
+
+    /// Rejects a count above `max_count`, which usually denotes a corrupted input
+    fn validate(&self, max_count: Option<u64>) -> anyhow::Result<()> {
+        match max_count {
+            Some(max) if self.count > max => {
+                bail!("{self} exceeds the maximum count of {max}")
+            }
+            _ => Ok(()),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -63,12 +102,24 @@ impl FromStr for Round {
         let withdraws = s
             .trim()
             .split(',')
-            .map(|w| w.parse().map_err(Into::into))
+            .map(|w| w.parse())
             .collect::<Result<Vec<_>, anyhow::Error>>()?;
         Ok(Self(withdraws))
     }
 }
 
+impl fmt::Display for Round {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, withdraw) in self.0.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{withdraw}")?;
+        }
+        Ok(())
+    }
+}
+
 impl Round {
     fn is_possible(&self, bag: &Bag) -> bool {
         self.0.iter().all(|w| w.is_possible(bag))
@@ -98,7 +149,7 @@ impl FromStr for Game {
             .next()
             .ok_or(anyhow!("missing game rounds"))?
             .split(';')
-            .map(|w| w.parse().map_err(Into::into))
+            .map(|w| w.parse())
             .collect::<Result<Vec<_>, anyhow::Error>>()?;
         Ok(Self {
             id: game_id,
@@ -107,36 +158,49 @@ impl FromStr for Game {
     }
 }
 
+/// Prints the game the way it is written in the input
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Game {}:", self.id)?;
+        for (idx, round) in self.rounds.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(";")?;
+            }
+            write!(f, " {round}")?;
+        }
+        Ok(())
+    }
+}
+
 impl Game {
     fn is_possible(&self, bag: &Bag) -> bool {
         self.rounds.iter().all(|r| r.is_possible(bag))
     }
 
-    fn bag(&self) -> Bag {
-        let red = self
-            .rounds
-            .iter()
-            .flat_map(|r| r.0.iter().filter(|w| w.color == Color::Red))
-            .map(|w| w.count)
-            .max()
-            .unwrap_or(0);
-
-        let green = self
-            .rounds
+    /// Checks that every withdraw of the game is at most `max_count`. No maximum means any count is
+    /// accepted
+    fn validate(&self, max_count: Option<u64>) -> anyhow::Result<()> {
+        self.rounds
             .iter()
-            .flat_map(|r| r.0.iter().filter(|w| w.color == Color::Green))
-            .map(|w| w.count)
-            .max()
-            .unwrap_or(0);
+            .flat_map(|r| r.0.iter())
+            .try_for_each(|w| w.validate(max_count))
+            .map_err(|e| anyhow!("game {}: {e}", self.id))
+    }
 
-        let blue = self
-            .rounds
+    /// The highest count withdrawn of every color, indexed by `Color::index`
+    pub(super) fn max_per_color(&self) -> [u64; 3] {
+        self.rounds
             .iter()
-            .flat_map(|r| r.0.iter().filter(|w| w.color == Color::Blue))
-            .map(|w| w.count)
-            .max()
-            .unwrap_or(0);
+            .flat_map(|r| r.0.iter())
+            .fold([0; 3], |mut maxima, w| {
+                let max = &mut maxima[w.color.index()];
+                *max = (*max).max(w.count);
+                maxima
+            })
+    }
 
+    fn bag(&self) -> Bag {
+        let [red, green, blue] = self.max_per_color();
         Bag { red, green, blue }
     }
 }
@@ -161,28 +225,205 @@ impl Bag {
     }
 }
 
+/// The fewest cubes of each color that make every game possible, one bag per game
+fn minimal_bags(games: &[Game]) -> Vec<Bag> {
+    games.iter().map(Game::bag).collect()
+}
+
 pub(super) struct Day2;
 impl super::day::Day for Day2 {
     type Item = Game;
     type Answer = u64;
+    type Context = Vec<Self::Item>;
 
     const DAY: usize = 2;
+    /// A game is parsed from its line, which is dropped right away
+    const STREAMING: bool = true;
 
-    fn part_1(games: Vec<Self::Item>) -> anyhow::Result<Self::Answer> {
+    fn validate_items(games: &[Self::Item], options: &Options) -> anyhow::Result<()> {
+        games
+            .iter()
+            .try_for_each(|game| game.validate(options.max_cubes))
+    }
+
+    fn part_1(games: &Self::Context) -> anyhow::Result<Self::Answer> {
         let bag = Bag {
             red: 12,
             green: 13,
             blue: 14,
         };
         let answer = games
-            .into_iter()
+            .iter()
             .filter_map(|g| g.is_possible(&bag).then_some(g.id as u64))
             .sum();
         Ok(answer)
     }
 
-    fn part_2(games: Vec<Self::Item>) -> anyhow::Result<Self::Answer> {
-        let answer = games.into_iter().map(|g| g.bag().power()).sum();
+    fn part_2(games: &Self::Context) -> anyhow::Result<Self::Answer> {
+        let answer = minimal_bags(games).iter().map(Bag::power).sum();
         Ok(answer)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        day::Day,
+        testing::{assert_round_trip, gen_input, Rng},
+    };
+
+    /// Only checks that parsing does not panic, whatever it returns
+    fn parse(s: &str) {
+        let _ = s.parse::<Game>();
+    }
+
+    #[test]
+    fn parse_edge_cases() {
+        const CASES: &[&str] = &[
+            "",
+            ":",
+            "Game",
+            "Game ",
+            "Game :",
+            "Game 1:",
+            "Game 1: ",
+            "Game 1: ;",
+            "Game 1: ,",
+            "Game 1: 1",
+            "Game 1: red 1",
+            "Game 1: 1  red",
+            "Game 1: 1 red;",
+            "Game 1: 18446744073709551616 red",
+            "Game 99999999999999999999: 1 red",
+            "Game -1: 1 red",
+            "Game 1: -1 red",
+            "Game 1: 1 red: 2 blue",
+            "Game 1: 1 rouge",
+            "Game 1: 1 réd, 2 🟦",
+            "Gäme 1: 1 red",
+        ];
+
+        for case in CASES {
+            parse(case);
+        }
+    }
+
+    #[test]
+    fn parse_random_input_never_panics() -> anyhow::Result<()> {
+        const ALPHABET: &[char] = &[
+            'G', 'a', 'm', 'e', ' ', ':', ';', ',', '0', '1', '9', '-', 'r', 'd', 'b', 'l', 'u',
+            'g', 'n', 'é', '\t',
+        ];
+
+        let mut rng = Rng::new(2);
+        for _ in 0..10_000 {
+            let len = rng.range(0, 40);
+            let s = (0..len).map(|_| *rng.pick(ALPHABET)).collect::<String>();
+            parse(&s);
+        }
+
+        // Valid games with a few characters altered
+        for game in gen_input(2, 2_000)?.lines() {
+            let mut chars = game.chars().collect::<Vec<_>>();
+            for _ in 0..rng.range(1, 4) {
+                let idx = rng.range(0, chars.len() as u64) as usize;
+                match rng.range(0, 3) {
+                    0 => {
+                        chars.remove(idx);
+                    }
+                    1 => chars.insert(idx, *rng.pick(ALPHABET)),
+                    _ => chars[idx] = *rng.pick(ALPHABET),
+                }
+            }
+            parse(&chars.into_iter().collect::<String>());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn withdraw_above_max_count() -> anyhow::Result<()> {
+        let withdraw: Withdraw = "101 red".parse()?;
+        assert!(withdraw.validate(Some(100)).is_err());
+        assert!(withdraw.validate(Some(101)).is_ok());
+        assert!(withdraw.validate(None).is_ok());
+
+        let game: Game = "Game 7: 3 blue; 101 red, 2 green".parse()?;
+        let err = game.validate(Some(100)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "game 7: 101 red exceeds the maximum count of 100"
+        );
+        assert!(game.validate(None).is_ok());
+
+        let games = [game];
+        assert!(Day2::validate_items(&games, &Options::default()).is_ok());
+        let options = Options {
+            max_cubes: Some(100),
+            ..Options::default()
+        };
+        assert!(Day2::validate_items(&games, &options).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn zero_withdraw_is_always_possible() -> anyhow::Result<()> {
+        let game: Game = "Game 1: 0 blue; 0 red, 0 green".parse()?;
+        let empty = Bag {
+            red: 0,
+            green: 0,
+            blue: 0,
+        };
+        assert!(game.is_possible(&empty));
+
+        Ok(())
+    }
+
+    #[test]
+    fn zero_withdraw_contributes_nothing_to_power() -> anyhow::Result<()> {
+        let game: Game = "Game 1: 3 red, 0 blue; 2 green".parse()?;
+        let bag = game.bag();
+        assert_eq!((bag.red, bag.green, bag.blue), (3, 2, 0));
+        assert_eq!(bag.power(), 0);
+
+        let game: Game = "Game 2: 3 red, 0 blue; 2 green, 4 blue".parse()?;
+        assert_eq!(game.bag().power(), 3 * 2 * 4);
+
+        Ok(())
+    }
+
+    #[test]
+    fn games_round_trip_through_display() -> anyhow::Result<()> {
+        assert_round_trip::<Day2>(&std::fs::read_to_string("src/day2/example.txt")?)?;
+        assert_round_trip::<Day2>(&gen_input(2, 200)?)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn sample_minimal_bags() -> anyhow::Result<()> {
+        let games = std::fs::read_to_string("src/day2/example.txt")?
+            .lines()
+            .map(Game::from_str)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let bags = minimal_bags(&games);
+        assert_eq!(bags.len(), 5);
+        assert_eq!((bags[0].red, bags[0].green, bags[0].blue), (4, 2, 6));
+        assert_eq!(bags[0].power(), 48);
+        assert_eq!(bags.iter().map(Bag::power).sum::<u64>(), 2286);
+
+        Ok(())
+    }
+
+    #[test]
+    fn max_per_color_of_sample_game() -> anyhow::Result<()> {
+        let game: Game = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green".parse()?;
+        assert_eq!(game.max_per_color(), [4, 2, 6]);
+        assert_eq!(game.max_per_color()[Color::Blue.index()], 6);
+        assert_eq!(game.bag().power(), 48);
+
+        Ok(())
+    }
+}
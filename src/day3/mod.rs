@@ -1,6 +1,8 @@
-use std::{collections::HashMap, str::FromStr};
+use std::str::FromStr;
 
-use anyhow::anyhow;
+use anyhow::bail;
+
+use crate::{grid::Grid, util};
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 enum RawPiece {
@@ -10,10 +12,7 @@ enum RawPiece {
 
 impl RawPiece {
     fn is_symbol(&self) -> bool {
-        match self {
-            Self::Char(c) if *c != '.' => true,
-            _ => false,
-        }
+        matches!(self, Self::Char(c) if *c != '.')
     }
 
     fn is_gear(&self) -> bool {
@@ -36,14 +35,35 @@ enum Piece {
     Char(char),
 }
 
+impl Piece {
+    /// The number of the piece, if it is one
+    fn value(&self) -> Option<u32> {
+        match self {
+            Self::Number(n, _) => Some(*n),
+            Self::Char(_) => None,
+        }
+    }
+
+    /// How many characters of the engine the piece covers
+    fn span(&self) -> usize {
+        match self {
+            Self::Number(_, len) => *len,
+            Self::Char(_) => 1,
+        }
+    }
+}
+
 fn lex(s: &str) -> Option<anyhow::Result<(Piece, &str)>> {
     let mut chars = s.char_indices();
 
     match chars.next() {
-        Some((start, c)) if c.is_digit(10) => {
-            let len = chars.by_ref().take_while(|(_, c)| c.is_digit(10)).count();
+        Some((start, c)) if c.is_ascii_digit() => {
+            let len = chars
+                .by_ref()
+                .take_while(|(_, c)| c.is_ascii_digit())
+                .count();
             let end = start + len + 1;
-            Some(match u32::from_str_radix(&s[start..end], 10) {
+            Some(match s[start..end].parse::<u32>() {
                 Ok(number) => Ok((Piece::Number(number, end - start), &s[end..])),
                 Err(e) => Err(e.into()),
             })
@@ -78,130 +98,278 @@ impl FromStr for Fragment {
     }
 }
 
-fn get_adjacent_indexes(x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> {
-    const ADJACENT_MATRIX: &'static [(i32, i32)] = &[
-        (0, -1),  // LEFT
-        (0, 1),   // RIGHT
-        (-1, 0),  // UP
-        (1, 0),   // DOWN
-        (-1, -1), // LEFT UP
-        (-1, 1),  // RIGHT UP
-        (1, -1),  // LEFT DOWN
-        (1, 1),   // RIGHT DOWN
-    ];
-
-    ADJACENT_MATRIX
-        .iter()
-        .flat_map(move |(offset_x, offset_y)| {
-            match (
-                x.checked_add_signed(*offset_x as isize),
-                y.checked_add_signed(*offset_y as isize),
-            ) {
-                (Some(x), Some(y)) => Some((x, y)),
-                _ => None,
-            }
-        })
+/// A number of the engine, located by its first digit
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(super) struct PartDetail {
+    number: u32,
+    row: usize,
+    column: usize,
+    len: usize,
+}
+
+/// A gear of the engine along with the two part numbers it is adjacent to
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(super) struct GearDetail {
+    row: usize,
+    column: usize,
+    numbers: Vec<u32>,
+}
+
+impl GearDetail {
+    fn ratio(&self) -> u32 {
+        self.numbers.iter().product()
+    }
 }
 
 #[derive(Debug)]
 pub(super) struct Engine {
     pieces: Vec<Piece>,
-    columns: usize,
-    raw: Vec<RawPiece>,
+    grid: Grid<RawPiece>,
 }
 
 impl Engine {
     fn craft(fragments: Vec<Fragment>) -> anyhow::Result<Self> {
-        let columns = fragments.first().ok_or(anyhow!("broken engine"))?.raw.len();
-        let pieces = fragments
-            .clone()
-            .into_iter()
-            .flat_map(|i| i.pieces)
-            .collect::<Vec<_>>();
-        let raw = fragments
-            .clone()
-            .into_iter()
-            .map(|i| i.raw)
-            .collect::<String>();
-        let raw = raw.chars().map(RawPiece::from).collect();
-        Ok(Engine {
-            pieces,
-            columns,
-            raw,
-        })
+        if fragments.is_empty() {
+            bail!("broken engine");
+        }
+
+        let grid = Grid::from_lines(fragments.iter().map(|f| &f.raw));
+        let pieces = fragments.into_iter().flat_map(|f| f.pieces).collect();
+        Ok(Engine { pieces, grid })
     }
 
-    fn parts(&self) -> Vec<u32> {
-        let mut parts = Vec::new();
+    /// All the numbers of the engine, with the position of their first digit
+    fn numbers(&self) -> impl Iterator<Item = PartDetail> + '_ {
         let mut raw_idx = 0usize;
 
-        for piece in self.pieces.iter() {
-            if let Piece::Number(n, len) = piece {
-                let len = *len;
+        self.pieces.iter().filter_map(move |piece| {
+            let start = raw_idx;
+            raw_idx += piece.span();
+
+            let number = piece.value()?;
+            let (row, column) = self.grid.position(start);
+            Some(PartDetail {
+                number,
+                row,
+                column,
+                len: piece.span(),
+            })
+        })
+    }
 
-                let (row, column) = self.map_index(raw_idx);
+    /// The positions of the cells surrounding every digit of a number
+    fn surroundings<'a>(
+        &'a self,
+        number: &PartDetail,
+    ) -> impl Iterator<Item = (usize, usize)> + 'a {
+        let (row, column) = (number.row, number.column);
+        (0..number.len).flat_map(move |y| {
+            self.grid
+                .neighbors8(row, column + y)
+                .map(|(x, y, _)| (x, y))
+        })
+    }
 
-                let mut adjacent_pieces = (0..len).flat_map(|y| {
-                    get_adjacent_indexes(row, column + y).filter_map(|(x, y)| self.get_raw(x, y))
-                });
+    fn is_part(&self, number: &PartDetail) -> bool {
+        self.surroundings(number)
+            .any(|(x, y)| self.get_raw_or_dot(x, y).is_symbol())
+    }
 
-                let is_part = adjacent_pieces.any(|p| p.is_symbol());
-                if is_part {
-                    parts.push(*n);
-                }
+    fn part_details(&self) -> Vec<PartDetail> {
+        self.numbers().filter(|n| self.is_part(n)).collect()
+    }
 
-                raw_idx += len;
-            } else {
-                raw_idx += 1;
-            }
-        }
+    fn parts(&self) -> Vec<u32> {
+        self.part_details().into_iter().map(|p| p.number).collect()
+    }
 
+    /// Every part number, duplicates included, in increasing order so that they can be compared
+    /// with the output of other solvers
+    #[cfg(test)]
+    pub(super) fn part_numbers(&self) -> Vec<u32> {
+        let mut parts = self.parts();
+        parts.sort_unstable();
         parts
     }
 
+    /// The part numbers whose span touches a border of the engine: its first or last row, or the
+    /// first or last column of their row
+    #[cfg(test)]
+    pub(super) fn edge_parts(&self) -> Vec<u32> {
+        let last_row = self.grid.rows() - 1;
+        self.part_details()
+            .into_iter()
+            .filter(|p| {
+                let row_len = self.grid.row(p.row).map_or(0, <[RawPiece]>::len);
+                p.row == 0 || p.row == last_row || p.column == 0 || p.column + p.len == row_len
+            })
+            .map(|p| p.number)
+            .collect()
+    }
+
+    /// Same as `parts`, scanning bands of rows in parallel. A number belongs to the band of the row
+    /// it is on, so that it is checked exactly once, while its surroundings are read from the whole
+    /// engine, across the seams of the bands
+    fn parts_parallel(&self, bands: usize) -> Vec<u32> {
+        let band_rows = self.grid.rows().div_ceil(bands.max(1));
+        let numbers = self.numbers().collect::<Vec<_>>();
+
+        std::thread::scope(|s| {
+            let handles = numbers
+                .chunk_by(|a, b| a.row / band_rows == b.row / band_rows)
+                .map(|band| {
+                    s.spawn(move || {
+                        band.iter()
+                            .filter(|n| self.is_part(n))
+                            .map(|n| n.number)
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            handles
+                .into_iter()
+                .flat_map(|h| h.join().expect("band scan panicked"))
+                .collect()
+        })
+    }
+
+    /// The number having a digit at `(x, y)`
+    fn number_at(&self, x: usize, y: usize) -> Option<PartDetail> {
+        let row = self.grid.row(x)?;
+        if !matches!(row.get(y)?, RawPiece::Digit(_)) {
+            return None;
+        }
+
+        let column = row[..y]
+            .iter()
+            .rposition(|p| !matches!(p, RawPiece::Digit(_)))
+            .map_or(0, |idx| idx + 1);
+
+        let digits = row[column..]
+            .iter()
+            .map_while(|p| match p {
+                RawPiece::Digit(d) => Some(*d),
+                RawPiece::Char(_) => None,
+            })
+            .collect::<Vec<_>>();
+
+        Some(PartDetail {
+            number: digits.iter().fold(0, |n, d| n * 10 + d),
+            row: x,
+            column,
+            len: digits.len(),
+        })
+    }
+
+    /// The numbers touching the cell at `pos`, each one once even when several of its digits do,
+    /// in the order in which they appear in the engine
+    fn numbers_adjacent_to(&self, pos: (usize, usize)) -> Vec<u32> {
+        let adjacent = self
+            .grid
+            .neighbors8(pos.0, pos.1)
+            .filter(|&(x, y, _)| matches!(self.get_raw_or_dot(x, y), RawPiece::Digit(_)))
+            .filter_map(|(x, y, _)| self.number_at(x, y));
+
+        let mut numbers = util::dedup_by_key(adjacent, |n| (n.row, n.column));
+        numbers.sort_by_key(|n| (n.row, n.column));
+        numbers.into_iter().map(|n| n.number).collect()
+    }
+
+    /// The gears are the `*` symbols adjacent to exactly two numbers
+    fn gear_details(&self) -> Vec<GearDetail> {
+        self.symbols()
+            .into_iter()
+            .filter(|(_, c)| RawPiece::Char(*c).is_gear())
+            .filter_map(|((row, column), _)| {
+                let numbers = self.numbers_adjacent_to((row, column));
+                (numbers.len() == 2).then_some(GearDetail {
+                    row,
+                    column,
+                    numbers,
+                })
+            })
+            .collect()
+    }
+
     fn gears(&self) -> Vec<u32> {
-        let mut parts = HashMap::new();
-        let mut raw_idx = 0usize;
+        self.gear_details().iter().map(GearDetail::ratio).collect()
+    }
 
-        for piece in self.pieces.iter() {
-            if let Piece::Number(n, len) = piece {
-                let len = *len;
-                let (row, column) = self.map_index(raw_idx);
+    /// Every symbol of the engine along with its position, row by row
+    fn symbols(&self) -> Vec<((usize, usize), char)> {
+        self.grid
+            .iter()
+            .filter_map(|(row, column, piece)| match piece {
+                RawPiece::Char(c) if piece.is_symbol() => Some(((row, column), *c)),
+                _ => None,
+            })
+            .collect()
+    }
 
-                for y in 0..len {
-                    let adjacent_gears = get_adjacent_indexes(row, column + y)
-                        .filter_map(|(x, y)| self.get_raw(x, y).map(|piece| (piece, (x, y))))
-                        .filter(|(p, _)| p.is_gear())
-                        .collect::<Vec<_>>();
+    /// Renders the engine as its original grid, with part numbers in green and gears in yellow
+    /// when colors are enabled
+    #[cfg(test)]
+    fn render_highlighted(&self) -> String {
+        self.render(util::term::color_enabled())
+    }
 
-                    if !adjacent_gears.is_empty() {
-                        for (_, index) in adjacent_gears {
-                            parts.entry(index).or_insert(Vec::new()).push(*n);
-                        }
+    #[cfg(test)]
+    fn render(&self, color: bool) -> String {
+        const GREEN: &str = "\x1b[32m";
+        const YELLOW: &str = "\x1b[33m";
+        const RESET: &str = "\x1b[0m";
+
+        let mut highlights = std::collections::HashMap::new();
+        if color {
+            for part in self.part_details() {
+                for y in 0..part.len {
+                    highlights.insert((part.row, part.column + y), GREEN);
+                }
+            }
 
-                        break;
+            for gear in self.gear_details() {
+                highlights.insert((gear.row, gear.column), YELLOW);
+            }
+        }
+
+        let mut rendered = String::with_capacity(self.grid.rows() * (self.grid.cols() + 1));
+        for row in 0..self.grid.rows() {
+            if row > 0 {
+                rendered.push('\n');
+            }
+
+            for (column, raw) in self.grid.row(row).unwrap_or_default().iter().enumerate() {
+                let c = match raw {
+                    RawPiece::Digit(d) => char::from_digit(*d, 10).unwrap_or('?'),
+                    RawPiece::Char(c) => *c,
+                };
+
+                match highlights.get(&(row, column)) {
+                    Some(highlight) => {
+                        rendered.push_str(highlight);
+                        rendered.push(c);
+                        rendered.push_str(RESET);
                     }
+                    None => rendered.push(c),
                 }
-
-                raw_idx += len;
-            } else {
-                raw_idx += 1;
             }
         }
 
-        parts
-            .into_values()
-            .filter(|g| g.len() == 2)
-            .map(|g| g.into_iter().product())
-            .collect()
+        rendered
     }
 
-    fn get_raw(&self, x: usize, y: usize) -> Option<RawPiece> {
-        self.raw.get(x * self.columns + y).copied()
+    /// The piece at `(x, y)`, reading an empty `.` outside of the engine
+    fn get_raw_or_dot(&self, x: usize, y: usize) -> RawPiece {
+        self.grid.get(x, y).copied().unwrap_or(RawPiece::Char('.'))
     }
+}
 
-    fn map_index(&self, idx: usize) -> (usize, usize) {
-        (idx / self.columns, idx % self.columns)
+impl TryFrom<Vec<Fragment>> for Engine {
+    type Error = anyhow::Error;
+
+    fn try_from(fragments: Vec<Fragment>) -> Result<Self, Self::Error> {
+        Engine::craft(fragments)
     }
 }
 
@@ -209,17 +377,24 @@ pub(super) struct Day3;
 impl super::day::Day for Day3 {
     type Item = Fragment;
     type Answer = u32;
+    type Context = Engine;
 
     const DAY: usize = 3;
 
-    fn part_1(items: Vec<Self::Item>) -> anyhow::Result<Self::Answer> {
-        let engine = Engine::craft(items)?;
-        let parts = engine.parts();
+    fn part_1(engine: &Self::Context) -> anyhow::Result<Self::Answer> {
+        /// The number of rows from which scanning in parallel pays off
+        const PARALLEL_ROWS: usize = 1000;
+
+        let parts = if engine.grid.rows() >= PARALLEL_ROWS && cfg!(not(target_arch = "wasm32")) {
+            let bands = std::thread::available_parallelism().map_or(1, |n| n.get());
+            engine.parts_parallel(bands)
+        } else {
+            engine.parts()
+        };
         Ok(parts.into_iter().sum())
     }
 
-    fn part_2(items: Vec<Self::Item>) -> anyhow::Result<Self::Answer> {
-        let engine = Engine::craft(items)?;
+    fn part_2(engine: &Self::Context) -> anyhow::Result<Self::Answer> {
         let gears = engine.gears();
         Ok(gears.into_iter().sum())
     }
@@ -237,6 +412,15 @@ mod test {
         assert_eq!(next, "..114..");
     }
 
+    #[test]
+    fn piece_value_and_span() {
+        assert_eq!(Piece::Number(467, 3).value(), Some(467));
+        assert_eq!(Piece::Number(467, 3).span(), 3);
+        assert_eq!(Piece::Char('.').value(), None);
+        assert_eq!(Piece::Char('.').span(), 1);
+        assert_eq!(Piece::Char('*').span(), 1);
+    }
+
     #[test]
     fn parse() -> anyhow::Result<()> {
         let s = "467..114..";
@@ -299,10 +483,310 @@ mod test {
     }
 
     #[test]
-    fn adjacent() {
+    fn gear_adjacent_to_several_digits() -> anyhow::Result<()> {
+        let fragments = ["123.", ".*..", ".45."]
+            .into_iter()
+            .map(Fragment::from_str)
+            .collect::<Result<Vec<_>, _>>()?;
+        let engine = Engine::craft(fragments)?;
+        assert_eq!(engine.gears(), vec![123 * 45]);
+
+        Ok(())
+    }
+
+    fn engine(rows: &[&str]) -> anyhow::Result<Engine> {
+        let fragments = rows
+            .iter()
+            .map(|r| Fragment::from_str(r))
+            .collect::<Result<Vec<_>, _>>()?;
+        Engine::craft(fragments)
+    }
+
+    #[test]
+    fn symbol_around_first_or_last_digit() -> anyhow::Result<()> {
+        // The number spans (1, 1) to (1, 3)
+        const FIRST: (usize, usize) = (1, 1);
+        const LAST: (usize, usize) = (1, 3);
+
+        let blank = engine(&[".....", ".123.", "....."])?;
+        assert!(blank.parts().is_empty());
+
+        for (row, column) in [FIRST, LAST] {
+            for (x, y, _) in blank.grid.neighbors8(row, column) {
+                if x == 1 && (1..=3).contains(&y) {
+                    continue;
+                }
+
+                let mut rows = vec![b".....".to_vec(), b".123.".to_vec(), b".....".to_vec()];
+                rows[x][y] = b'#';
+                let rows = rows
+                    .into_iter()
+                    .map(String::from_utf8)
+                    .collect::<Result<Vec<_>, _>>()?;
+                let rows = rows.iter().map(String::as_str).collect::<Vec<_>>();
+
+                assert_eq!(engine(&rows)?.parts(), vec![123], "{rows:?}");
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn symbol_does_not_wrap_around_rows() -> anyhow::Result<()> {
+        assert!(engine(&["...12", "#...."])?.parts().is_empty());
+        assert!(engine(&["....#", "12..."])?.parts().is_empty());
+        assert!(engine(&["...12", ".....", "#...."])?.parts().is_empty());
+
+        Ok(())
+    }
+
+    fn sample_engine() -> anyhow::Result<Engine> {
+        let fragments = std::fs::read_to_string("src/day3/example.txt")?
+            .lines()
+            .map(Fragment::from_str)
+            .collect::<Result<Vec<_>, _>>()?;
+        Engine::craft(fragments)
+    }
+
+    #[test]
+    fn gear_details_are_sorted() -> anyhow::Result<()> {
+        let engine = sample_engine()?;
+        assert_eq!(
+            engine.gear_details(),
+            vec![
+                GearDetail {
+                    row: 1,
+                    column: 3,
+                    numbers: vec![467, 35],
+                },
+                GearDetail {
+                    row: 8,
+                    column: 5,
+                    numbers: vec![755, 598],
+                },
+            ]
+        );
+        assert_eq!(engine.gears(), vec![16345, 451490]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn sample_part_numbers_are_sorted() -> anyhow::Result<()> {
+        assert_eq!(
+            sample_engine()?.part_numbers(),
+            [35, 467, 592, 598, 617, 633, 664, 755]
+        );
+
+        let engine = engine(&["12*12", "....."])?;
+        assert_eq!(engine.part_numbers(), [12, 12]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn parts_on_the_edges() -> anyhow::Result<()> {
+        let corners = engine(&["12....", "..*.7.", "3.*45.", "....#8"])?;
+        assert_eq!(corners.edge_parts(), [12, 8]);
+
+        let top = engine(&["..7..", ".*3*.", "....."])?;
+        assert_eq!(top.edge_parts(), [7]);
+
+        assert_eq!(sample_engine()?.edge_parts(), [467, 617, 664, 598]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn neighbors_at_the_corners() -> anyhow::Result<()> {
+        let engine = engine(&["1*.", "23.", "..#"])?;
+
+        let neighbors = |row, column| {
+            let mut neighbors = engine
+                .grid
+                .neighbors8(row, column)
+                .map(|(x, y, p)| (x, y, *p))
+                .collect::<Vec<_>>();
+            neighbors.sort_by_key(|&(x, y, _)| (x, y));
+            neighbors
+        };
+
+        assert_eq!(
+            neighbors(0, 0),
+            [
+                (0, 1, RawPiece::Char('*')),
+                (1, 0, RawPiece::Digit(2)),
+                (1, 1, RawPiece::Digit(3)),
+            ]
+        );
+        assert_eq!(
+            neighbors(2, 2),
+            [
+                (1, 1, RawPiece::Digit(3)),
+                (1, 2, RawPiece::Char('.')),
+                (2, 1, RawPiece::Char('.')),
+            ]
+        );
+        assert_eq!(neighbors(1, 1).len(), 8);
+
+        Ok(())
+    }
+
+    #[test]
+    fn outside_of_the_engine_is_a_dot() -> anyhow::Result<()> {
+        let engine = engine(&["1*", "23"])?;
+        assert_eq!(engine.get_raw_or_dot(0, 1), RawPiece::Char('*'));
+        assert_eq!(engine.get_raw_or_dot(1, 1), RawPiece::Digit(3));
+        for (x, y) in [(0, 2), (2, 0), (5, 5), (usize::MAX, 0)] {
+            assert_eq!(engine.get_raw_or_dot(x, y), RawPiece::Char('.'), "{x} {y}");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn sample_symbols() -> anyhow::Result<()> {
+        assert_eq!(
+            sample_engine()?.symbols(),
+            [
+                ((1, 3), '*'),
+                ((3, 6), '#'),
+                ((4, 3), '*'),
+                ((5, 5), '+'),
+                ((8, 3), '$'),
+                ((8, 5), '*'),
+            ]
+        );
+        assert!(engine(&["1.2", "..."])?.symbols().is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn numbers_adjacent_to_a_symbol() -> anyhow::Result<()> {
+        let two = engine(&["12...", "..*..", "...34"])?;
+        assert_eq!(two.numbers_adjacent_to((1, 2)), [12, 34]);
+        assert_eq!(two.gears(), [12 * 34]);
+
+        let three = engine(&["12.34", "..*..", ".5..."])?;
+        assert_eq!(three.numbers_adjacent_to((1, 2)), [12, 34, 5]);
+        assert!(three.gears().is_empty());
+
+        // Equal numbers are still distinct numbers
+        let twins = engine(&["11.11", "..*.."])?;
+        assert_eq!(twins.numbers_adjacent_to((1, 2)), [11, 11]);
+
+        // A number touching the cell with all of its digits is only reported once
+        let long = engine(&["123", ".*."])?;
+        assert_eq!(long.numbers_adjacent_to((1, 1)), [123]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn render_highlighted_depends_on_the_terminal() -> anyhow::Result<()> {
+        let engine = engine(&["12.", ".*.", "3.."])?;
+        let rendered = engine.render_highlighted();
+        assert!(rendered == engine.render(false) || rendered == engine.render(true));
+
+        Ok(())
+    }
+
+    #[test]
+    fn render_without_color() -> anyhow::Result<()> {
+        let input = std::fs::read_to_string("src/day3/example.txt")?;
+        let fragments = input
+            .lines()
+            .map(Fragment::from_str)
+            .collect::<Result<Vec<_>, _>>()?;
+        let engine = Engine::craft(fragments)?;
+        assert_eq!(engine.render(false), input.trim_end());
+
+        Ok(())
+    }
+
+    #[test]
+    fn render_with_color() -> anyhow::Result<()> {
+        let fragments = ["12.", ".*.", "3.."]
+            .into_iter()
+            .map(Fragment::from_str)
+            .collect::<Result<Vec<_>, _>>()?;
+        let engine = Engine::craft(fragments)?;
         assert_eq!(
-            get_adjacent_indexes(0, 1).collect::<Vec<(_, _)>>(),
-            vec![(0, 0), (0, 2), (1, 1), (1, 0), (1, 2)]
+            engine.render(true),
+            "\x1b[32m1\x1b[0m\x1b[32m2\x1b[0m.\n.\x1b[33m*\x1b[0m.\n\x1b[32m3\x1b[0m.."
         );
+
+        Ok(())
+    }
+
+    #[test]
+    fn parallel_parts_match_sequential() -> anyhow::Result<()> {
+        let input = crate::testing::gen_input(3, 300)?;
+        let items = crate::testing::parse_items::<Day3>(&input)?;
+        let engine = Engine::craft(items)?;
+
+        let sequential = engine.parts();
+        for bands in [1, 2, 7, 64, 300, 1000] {
+            assert_eq!(engine.parts_parallel(bands), sequential, "{bands} bands");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn parallel_parts_across_seams() -> anyhow::Result<()> {
+        // With two bands, the seam is between the second and the third row
+        // The symbol is read by a number on each side of the seam
+        let above = engine(&["12....", "...34.", "..*...", "56...7"])?;
+        assert_eq!(above.parts(), [34, 56]);
+        assert_eq!(above.parts_parallel(2), [34, 56]);
+
+        let below = engine(&["......", "..*...", ".12...", "......"])?;
+        assert_eq!(below.parts_parallel(2), [12]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn ragged_rows() -> anyhow::Result<()> {
+        let fragments = ["467..", "...*......", "", "35"]
+            .into_iter()
+            .map(Fragment::from_str)
+            .collect::<Result<Vec<_>, _>>()?;
+        let engine = Engine::craft(fragments)?;
+
+        assert_eq!(engine.grid.get(0, 0).copied(), Some(RawPiece::Digit(4)));
+        assert_eq!(engine.grid.get(0, 4).copied(), Some(RawPiece::Char('.')));
+        assert_eq!(engine.grid.get(0, 5).copied(), None);
+        assert_eq!(engine.grid.get(1, 3).copied(), Some(RawPiece::Char('*')));
+        assert_eq!(engine.grid.get(1, 9).copied(), Some(RawPiece::Char('.')));
+        assert_eq!(engine.grid.get(1, 10).copied(), None);
+        assert_eq!(engine.grid.get(2, 0).copied(), None);
+        assert_eq!(engine.grid.get(3, 1).copied(), Some(RawPiece::Digit(5)));
+        assert_eq!(engine.grid.get(4, 0).copied(), None);
+
+        assert_eq!(engine.grid.position(5), (1, 0));
+        assert_eq!(engine.grid.position(15), (3, 0));
+        assert_eq!(engine.parts(), vec![467]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn adjacency_across_rows_of_differing_widths() -> anyhow::Result<()> {
+        // The symbol is only diagonal to the number when rows are not read at a fixed width
+        let wider_below = engine(&["12", "..*"])?;
+        assert_eq!(wider_below.parts(), [12]);
+
+        let narrower_above = engine(&["*", "..5"])?;
+        assert!(narrower_above.parts().is_empty());
+
+        let narrower_below = engine(&["...45", "*"])?;
+        assert!(narrower_below.parts().is_empty());
+        assert_eq!(narrower_below.grid.neighbors8(1, 0).count(), 2);
+
+        Ok(())
     }
 }
@@ -1,60 +1,144 @@
 use std::{collections::HashSet, str::FromStr};
 
-use anyhow::anyhow;
+use anyhow::{anyhow, bail};
+
+use crate::util;
 
 #[derive(Debug, Clone)]
 pub(super) struct ScratchCard {
-    winning: HashSet<u32>,
-    numbers: HashSet<u32>,
-    copies: u32,
+    /// The sections of numbers separated by `|`, the first one holding the winning numbers. A card
+    /// usually has a single other section, the numbers you have
+    sections: Vec<HashSet<u32>>,
+}
+
+/// Parses a section of numbers, which may be empty
+fn parse_numbers(section: &str) -> anyhow::Result<HashSet<u32>> {
+    section
+        .split_whitespace()
+        .map(|n| n.parse().map_err(|e| anyhow!("invalid number `{n}`: {e}")))
+        .collect()
 }
 
 impl FromStr for ScratchCard {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut parts = s.split(":");
-        let start = parts.next().ok_or(anyhow!("missing start"))?;
-
-        let _card_id = start
-            .strip_prefix("Card")
-            .ok_or(anyhow!("card should start with Card"))?
-            .trim();
-
-        let content = parts.next().ok_or(anyhow!("numbers numbers"))?;
-        let mut content = content.split("|");
-
-        let winning = content.next().ok_or(anyhow!("missing winning numbers"))?;
-        let winning = winning
-            .trim()
-            .split(" ")
-            .filter(|n| !n.is_empty())
-            .map(|n| n.trim().parse())
-            .collect::<Result<HashSet<_>, _>>()?;
-
-        let numbers = content.next().ok_or(anyhow!("missing numbers"))?;
-        let numbers = numbers
-            .trim()
-            .split(" ")
-            .filter(|n| !n.is_empty())
-            .map(|n| n.trim().parse())
-            .collect::<Result<HashSet<_>, _>>()?;
-
-        Ok(Self {
-            winning,
-            numbers,
-            copies: 0,
-        })
+        let content = util::after_colon(util::strip_comment(s), "Card")?;
+        let sections = content
+            .split('|')
+            .map(parse_numbers)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        if sections.len() < 2 {
+            bail!("missing numbers");
+        }
+
+        Ok(Self { sections })
     }
 }
 
 impl ScratchCard {
+    fn winning(&self) -> &HashSet<u32> {
+        &self.sections[0]
+    }
+
+    /// The winning numbers found in every section after the first one
     fn winning_numbers<'a>(&'a self) -> impl Iterator<Item = u32> + 'a {
-        self.numbers.intersection(&self.winning).copied()
+        self.sections[1..]
+            .iter()
+            .flat_map(|numbers| numbers.intersection(self.winning()).copied())
+    }
+
+    /// How many winning numbers are found in each section after the first one
+    #[allow(dead_code)]
+    fn matches_per_section(&self) -> Vec<usize> {
+        self.sections[1..]
+            .iter()
+            .map(|numbers| numbers.intersection(self.winning()).count())
+            .collect()
+    }
+}
+
+/// How a card with winning numbers is scored
+#[allow(dead_code)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Scoring {
+    /// One point per winning number
+    Linear,
+    /// One point for the first winning number, then multiplied by `base` for every other one
+    Exponential { base: u32 },
+}
+
+impl Default for Scoring {
+    fn default() -> Self {
+        Self::Exponential { base: 2 }
+    }
+}
+
+impl Scoring {
+    fn points(&self, matches: u32) -> u32 {
+        match (self, matches) {
+            (_, 0) => 0,
+            (Self::Linear, matches) => matches,
+            (Self::Exponential { base }, matches) => base.pow(matches - 1),
+        }
+    }
+}
+
+/// How many winning numbers every card has, counted once for both parts
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(super) struct Matches(Vec<usize>);
+
+impl Matches {
+    fn of(cards: &[ScratchCard]) -> Self {
+        Self(cards.iter().map(|c| c.winning_numbers().count()).collect())
+    }
+
+    fn points(&self, scoring: Scoring) -> u32 {
+        self.0.iter().map(|m| scoring.points(*m as u32)).sum()
+    }
+
+    /// How many cards are held in the end. A card wins a copy of each of the next cards, as many
+    /// as it has matches, for each of its own copies
+    fn total_cards(&self) -> anyhow::Result<u32> {
+        let mut copies = vec![1; self.0.len()];
+        for (idx, matches) in self.0.iter().enumerate() {
+            let count = copies[idx];
+            let won = copies
+                .get_mut(idx + 1..idx + 1 + matches)
+                .ok_or(anyhow!("missing cards"))?;
+            for won in won {
+                *won += count;
+            }
+        }
+
+        Ok(copies.into_iter().sum())
     }
+}
+
+impl From<Vec<ScratchCard>> for Matches {
+    fn from(cards: Vec<ScratchCard>) -> Self {
+        Self::of(&cards)
+    }
+}
 
-    fn count(&self) -> u32 {
-        1 + self.copies
+#[cfg(test)]
+fn score_cards(cards: &[ScratchCard], scoring: Scoring) -> u32 {
+    Matches::of(cards).points(scoring)
+}
+
+/// The points of the cards along with how many cards are held in the end, the matches of every
+/// card being counted once for both
+#[cfg(test)]
+fn solve_both(cards: &[ScratchCard]) -> anyhow::Result<(u32, u32)> {
+    let matches = Matches::of(cards);
+    Ok((matches.points(Scoring::default()), matches.total_cards()?))
+}
+
+fn plural(count: u32, suffix: &str) -> &str {
+    if count == 1 {
+        ""
+    } else {
+        suffix
     }
 }
 
@@ -62,47 +146,154 @@ pub(super) struct Day4;
 impl super::day::Day for Day4 {
     type Item = ScratchCard;
     type Answer = u32;
+    type Context = Matches;
 
     const DAY: usize = 4;
 
-    fn part_1(cards: Vec<Self::Item>) -> anyhow::Result<Self::Answer> {
-        let answer = cards
-            .into_iter()
-            .filter_map(|c| {
-                let winning_numbers = c.winning_numbers().count() as u32;
-                (winning_numbers > 0).then(|| 2u32.pow(winning_numbers - 1))
+    fn explain(cards: &[Self::Item]) -> Vec<String> {
+        cards
+            .iter()
+            .enumerate()
+            .map(|(idx, c)| {
+                let matches = c.winning_numbers().count() as u32;
+                let points = Scoring::default().points(matches);
+                format!(
+                    "Card {} had {matches} match{} → {points} point{}",
+                    idx + 1,
+                    plural(matches, "es"),
+                    plural(points, "s")
+                )
             })
-            .sum();
-        Ok(answer)
+            .collect()
     }
 
-    fn part_2(mut cards: Vec<Self::Item>) -> anyhow::Result<Self::Answer> {
-        let copies = cards
-            .iter()
-            .map(|c| c.winning_numbers().count())
-            .collect::<Vec<_>>();
+    fn part_1(matches: &Self::Context) -> anyhow::Result<Self::Answer> {
+        Ok(matches.points(Scoring::default()))
+    }
 
-        for (idx, copies) in copies.into_iter().enumerate() {
-            let won_cards = cards
-                .get_mut(idx + 1..idx + 1 + copies)
-                .ok_or(anyhow!("missing cards"))?;
+    fn part_2(matches: &Self::Context) -> anyhow::Result<Self::Answer> {
+        matches.total_cards()
+    }
+}
 
-            for won_card in won_cards {
-                won_card.copies += 1;
-            }
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::day::Day;
 
-            let current = &cards[idx];
-            for _ in 0..current.copies {
-                let won_cards = cards
-                    .get_mut(idx + 1..idx + 1 + copies)
-                    .ok_or(anyhow!("missing cards"))?;
+    fn sample() -> anyhow::Result<Vec<ScratchCard>> {
+        std::fs::read_to_string("src/day4/example.txt")?
+            .lines()
+            .map(ScratchCard::from_str)
+            .collect()
+    }
 
-                for won_card in won_cards {
-                    won_card.copies += 1;
-                }
-            }
+    #[test]
+    fn empty_sections() -> anyhow::Result<()> {
+        for card in [
+            "Card 1: | 1 2 3",
+            "Card 1: 1 2 3 |",
+            "Card 1: |",
+            "Card 1:|",
+        ] {
+            let card = ScratchCard::from_str(card)?;
+            assert_eq!(card.winning_numbers().count(), 0);
+            assert_eq!(score_cards(&[card], Scoring::default()), 0);
         }
 
-        Ok(cards.into_iter().map(|c| c.count()).sum())
+        let card = ScratchCard::from_str("Card 1: | 1 2 3")?;
+        assert!(card.winning().is_empty());
+        assert_eq!(card.sections[1], HashSet::from([1, 2, 3]));
+
+        let card = ScratchCard::from_str("Card 1: 1 2 | 2 3 # one match")?;
+        assert_eq!(card.sections[1], HashSet::from([2, 3]));
+        assert_eq!(card.winning_numbers().count(), 1);
+
+        assert!(ScratchCard::from_str("Card 1: 1 2 3").is_err());
+        assert!(ScratchCard::from_str("Card 1: 1 x | 3").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn sections() -> anyhow::Result<()> {
+        let card = ScratchCard::from_str("Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53")?;
+        assert_eq!(card.sections.len(), 2);
+        assert_eq!(card.matches_per_section(), [4]);
+
+        let card = ScratchCard::from_str("Card 1: 1 2 3 | 1 4 5 | 2 3 6 7")?;
+        assert_eq!(card.sections.len(), 3);
+        assert_eq!(card.winning(), &HashSet::from([1, 2, 3]));
+        assert_eq!(card.matches_per_section(), [1, 2]);
+        assert_eq!(card.winning_numbers().count(), 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn scoring_sample() -> anyhow::Result<()> {
+        let cards = sample()?;
+        assert_eq!(score_cards(&cards, Scoring::default()), 13);
+        assert_eq!(score_cards(&cards, Scoring::Linear), 4 + 2 + 2 + 1);
+        assert_eq!(
+            score_cards(&cards, Scoring::Exponential { base: 3 }),
+            27 + 3 + 3 + 1
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn solve_both_parts_of_sample() -> anyhow::Result<()> {
+        let cards = sample()?;
+        assert_eq!(solve_both(&cards)?, (13, 30));
+        let matches = Day4::build_context(cards)?;
+        assert_eq!(matches, Matches(vec![4, 2, 2, 1, 0, 0]));
+        assert_eq!(Day4::part_1(&matches)?, 13);
+        assert_eq!(Day4::part_2(&matches)?, 30);
+
+        // The last card can't win cards past the end of the table
+        let cards = [ScratchCard::from_str("Card 1: 1 | 1")?];
+        assert!(solve_both(&cards).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn part_2_on_a_long_chain_of_cards() -> anyhow::Result<()> {
+        // Every card but the last wins a copy of the next one, so the n-th card is held n times
+        const CARDS: u32 = 2_000;
+        let cards = (1..=CARDS)
+            .map(|id| {
+                let mine = if id == CARDS { 2 } else { 1 };
+                ScratchCard::from_str(&format!("Card {id}: 1 | {mine}"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        assert_eq!(
+            Day4::part_2(&Day4::build_context(cards)?)?,
+            CARDS * (CARDS + 1) / 2
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn explain_sample() -> anyhow::Result<()> {
+        let cards = sample()?;
+
+        assert_eq!(
+            Day4::explain(&cards),
+            vec![
+                "Card 1 had 4 matches → 8 points",
+                "Card 2 had 2 matches → 2 points",
+                "Card 3 had 2 matches → 2 points",
+                "Card 4 had 1 match → 1 point",
+                "Card 5 had 0 matches → 0 points",
+                "Card 6 had 0 matches → 0 points",
+            ]
+        );
+
+        Ok(())
     }
 }
@@ -1,7 +1,9 @@
-use std::{str::FromStr, time::Instant};
+use std::{collections::BinaryHeap, str::FromStr};
 
 use anyhow::{anyhow, bail};
 
+use crate::{day::Options, util};
+
 #[derive(Debug, Clone)]
 struct Path {
     source: String,
@@ -32,7 +34,7 @@ impl FromStr for Path {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 struct MapRange {
     destination_start: u64,
     source_start: u64,
@@ -42,12 +44,21 @@ struct MapRange {
 impl MapRange {
     fn map(&self, n: u64) -> Option<u64> {
         let (range_start, range_end) = self.range();
-        (n >= range_start && n <= range_end).then(|| self.destination_start + n - range_start)
+        (n >= range_start && n < range_end).then(|| self.destination_start + n - range_start)
     }
 
+    /// The half-open range of the sources
     fn range(&self) -> (u64, u64) {
         (self.source_start, self.source_start + self.len)
     }
+
+    /// The source mapped to `n`, when `n` is in the destination of the range
+    #[allow(dead_code)]
+    fn unmap(&self, n: u64) -> Option<u64> {
+        let destination_end = self.destination_start + self.len;
+        (n >= self.destination_start && n < destination_end)
+            .then(|| self.source_start + n - self.destination_start)
+    }
 }
 
 impl FromStr for MapRange {
@@ -82,11 +93,81 @@ impl FromStr for MapRange {
 struct Map {
     category: Path,
     ranges: Vec<MapRange>,
+    /// The lowest start and highest end, excluded, of the source ranges
+    bounds: (u64, u64),
 }
 
 impl Map {
     fn map(&self, n: u64) -> Option<u64> {
-        self.ranges.iter().find_map(|r| r.map(n))
+        self.map_with_range(n).map(|(n, _)| n)
+    }
+
+    /// Maps `n`, also returning the range responsible for the mapping
+    fn map_with_range(&self, n: u64) -> Option<(u64, &MapRange)> {
+        let (start, end) = self.bounds;
+        if n < start || n >= end {
+            return None;
+        }
+
+        self.ranges
+            .iter()
+            .find_map(|r| r.map(n).map(|mapped| (mapped, r)))
+    }
+
+    /// Every source mapped to `n`: the sources of the ranges whose destination holds `n`, and `n`
+    /// itself when it is outside of every source range
+    #[allow(dead_code)]
+    fn unmap(&self, n: u64) -> Vec<u64> {
+        let mut sources = self
+            .ranges
+            .iter()
+            .filter_map(|r| r.unmap(n))
+            .collect::<Vec<_>>();
+
+        let in_sources = self.ranges.iter().any(|r| {
+            let (start, end) = r.range();
+            n >= start && n < end
+        });
+        if !in_sources {
+            sources.push(n);
+        }
+
+        sources
+    }
+
+    /// Maps the half-open interval `[start, end)`, splitting it where it crosses the boundaries of
+    /// the ranges. The parts outside of every range map to themselves
+    fn map_interval(&self, start: u64, end: u64) -> Vec<(u64, u64)> {
+        let mut ranges = self.ranges.iter().collect::<Vec<_>>();
+        ranges.sort_by_key(|r| r.source_start);
+
+        let mut mapped = Vec::new();
+        let mut cursor = start;
+        for range in ranges {
+            let (range_start, range_end) = range.range();
+            if cursor >= end || range_start >= end {
+                break;
+            }
+            if range_end <= cursor {
+                continue;
+            }
+
+            if cursor < range_start {
+                mapped.push((cursor, range_start));
+                cursor = range_start;
+            }
+
+            let until = range_end.min(end);
+            let mapped_start = range.destination_start + cursor - range_start;
+            mapped.push((mapped_start, mapped_start + until - cursor));
+            cursor = until;
+        }
+
+        if cursor < end {
+            mapped.push((cursor, end));
+        }
+
+        mapped
     }
 }
 
@@ -102,11 +183,17 @@ impl TryFrom<Vec<String>> for Map {
             .ok_or(anyhow!("missing path"))
             .and_then(Path::from_str)?;
 
-        let ranges = items.map(|i| i.parse()).collect::<Result<Vec<_>, _>>()?;
+        let ranges = items
+            .map(|i| i.parse::<MapRange>())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let start = ranges.iter().map(|r| r.source_start).min().unwrap_or(0);
+        let end = ranges.iter().map(|r| r.range().1).max().unwrap_or(0);
 
         Ok(Self {
             category: path,
             ranges,
+            bounds: (start, end),
         })
     }
 }
@@ -117,15 +204,10 @@ struct Almanac {
 }
 
 impl Almanac {
-    fn create(blocks: &[String]) -> anyhow::Result<Almanac> {
+    fn create(blocks: &[&[String]]) -> anyhow::Result<Almanac> {
         let maps = blocks
-            .split(|b| b.is_empty())
-            .map(|b| b.into_iter().map(|s| s.to_string()).collect::<Vec<_>>())
-            .collect::<Vec<_>>();
-
-        let maps = maps
-            .into_iter()
-            .map(Map::try_from)
+            .iter()
+            .map(|b| Map::try_from(b.to_vec()))
             .collect::<Result<Vec<_>, _>>()?;
 
         Ok(Self { maps })
@@ -136,6 +218,29 @@ impl Almanac {
     }
 
     fn resolve(&self, seed: u64, source: &str, destination: &str) -> u64 {
+        self.resolve_with(seed, source, destination, |_, _| {})
+    }
+
+    /// Every value `seed` goes through on its way to a location, starting with the seed itself,
+    /// along with its category
+    #[allow(dead_code)]
+    fn resolve_trace(&self, seed: u64) -> Vec<(String, u64)> {
+        let mut trace = vec![("seed".to_string(), seed)];
+        self.resolve_with(seed, "seed", "location", |category, value| {
+            trace.push((category.to_string(), value))
+        });
+        trace
+    }
+
+    /// Resolves `seed` from the `source` category to the `destination` one, calling `hop` with
+    /// every category reached along with the value in that category
+    fn resolve_with(
+        &self,
+        seed: u64,
+        source: &str,
+        destination: &str,
+        mut hop: impl FnMut(&str, u64),
+    ) -> u64 {
         let mut dest = seed;
         let mut next_map = source;
 
@@ -145,11 +250,7 @@ impl Almanac {
             if let Some(n) = map.map(dest) {
                 dest = n;
             }
-
-            // println!(
-            //     "{} -> {} -> {dest} (-> {next_map})",
-            //     map.category.source, map.category.destination
-            // );
+            hop(next_map, dest);
 
             if map.category.destination == destination {
                 break;
@@ -158,6 +259,114 @@ impl Almanac {
 
         dest
     }
+
+    /// The lowest seed that resolves to `location`, found by unmapping it through the categories in
+    /// reverse, or `None` if no seed does
+    #[allow(dead_code)]
+    fn seed_for_location(&self, location: u64) -> Option<u64> {
+        let mut values = vec![location];
+        let mut category = "location";
+
+        while category != "seed" {
+            let map = self
+                .maps
+                .iter()
+                .find(|m| m.category.destination == category)?;
+
+            values = values.into_iter().flat_map(|n| map.unmap(n)).collect();
+            values.sort_unstable();
+            values.dedup();
+            category = map.category.source.as_str();
+        }
+
+        values.first().copied()
+    }
+
+    /// The intervals of locations of the seeds in `[start, end)`, along with the most intervals held
+    /// at once while mapping them through the categories. The intervals are merged after every
+    /// category, so that each boundary of a range splits at most one of them
+    fn location_intervals(&self, start: u64, end: u64) -> (Vec<(u64, u64)>, usize) {
+        let mut intervals = vec![(start, end)];
+        let mut peak = intervals.len();
+        let mut category = "seed";
+
+        while let Some(map) = self.map(category) {
+            let mapped = intervals
+                .iter()
+                .flat_map(|&(start, end)| map.map_interval(start, end))
+                .collect::<Vec<_>>();
+            peak = peak.max(mapped.len());
+            intervals = merge_intervals(mapped);
+
+            category = map.category.destination.as_str();
+            if category == "location" {
+                break;
+            }
+        }
+
+        (intervals, peak)
+    }
+
+    /// The lowest location of the seeds of the half-open `ranges`, along with the most intervals
+    /// held at once. The ranges are mapped one after the other, folding their lowest location into
+    /// a running minimum, so that only the intervals of a single range are ever held
+    fn lowest_location_with_peak(
+        &self,
+        ranges: impl IntoIterator<Item = (u64, u64)>,
+    ) -> (Option<u64>, usize) {
+        ranges
+            .into_iter()
+            .fold((None, 0), |(lowest, peak), (start, end)| {
+                let (intervals, held) = self.location_intervals(start, end);
+                let lowest = lowest
+                    .into_iter()
+                    .chain(intervals.first().map(|i| i.0))
+                    .min();
+                (lowest, peak.max(held))
+            })
+    }
+
+    /// The lowest location of the seeds of the half-open `ranges`, mapping whole intervals of
+    /// seeds instead of every seed
+    fn lowest_location_of_ranges(
+        &self,
+        ranges: impl IntoIterator<Item = (u64, u64)>,
+    ) -> Option<u64> {
+        self.lowest_location_with_peak(ranges).0
+    }
+
+    /// The `k` lowest locations of `seeds`, in increasing order
+    fn lowest_k_locations(&self, seeds: impl IntoIterator<Item = u64>, k: usize) -> Vec<u64> {
+        // A max-heap whose top is the highest of the lowest locations found so far
+        let mut lowest = BinaryHeap::with_capacity(k + 1);
+
+        for seed in seeds {
+            let location = self.resolve(seed, "seed", "location");
+            if lowest.len() < k {
+                lowest.push(location);
+            } else if lowest.peek().is_some_and(|highest| location < *highest) {
+                lowest.pop();
+                lowest.push(location);
+            }
+        }
+
+        lowest.into_sorted_vec()
+    }
+}
+
+/// Sorts the half-open `intervals` and merges those that overlap or touch
+fn merge_intervals(mut intervals: Vec<(u64, u64)>) -> Vec<(u64, u64)> {
+    intervals.retain(|(start, end)| start < end);
+    intervals.sort_unstable();
+
+    let mut merged: Vec<(u64, u64)> = Vec::with_capacity(intervals.len());
+    for (start, end) in intervals {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
 }
 
 struct Seeds(Vec<u64>);
@@ -176,13 +385,11 @@ impl FromStr for Seeds {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (_, seeds) = s.split_once(":").ok_or(anyhow!("missing seeds"))?;
+        let (label, seeds) = util::parse_labeled_numbers(s)?;
+        if label != "seeds" {
+            bail!("expected `seeds`, got `{label}`");
+        }
 
-        let seeds = seeds
-            .trim()
-            .split(" ")
-            .map(|s| s.parse())
-            .collect::<Result<Vec<_>, _>>()?;
         Ok(Self(seeds))
     }
 }
@@ -194,28 +401,57 @@ struct Worker {
 }
 
 impl Worker {
-    fn run(self) -> u64 {
-        println!(
-            "Start working for range ({}, {})",
-            self.range.0, self.range.1
-        );
+    /// A worker for every range of `seeds`. Their ids follow the order of the starts of the ranges,
+    /// so that they are the same from one run to the other
+    fn for_ranges(seeds: &Seeds, almanac: &Almanac) -> Vec<Worker> {
+        let mut ranges = seeds.ranges().collect::<Vec<_>>();
+        ranges.sort();
+
+        ranges
+            .into_iter()
+            .enumerate()
+            .map(|(id, range)| Worker {
+                id,
+                almanac: almanac.clone(),
+                range,
+            })
+            .collect()
+    }
 
-        let start = Instant::now();
+    /// Prints how many of its seeds the worker resolved, every half second
+    #[cfg(not(target_arch = "wasm32"))]
+    fn progress(&self) -> impl FnMut(usize) {
+        let (id, seed_count) = (self.id, self.range.1 - self.range.0);
+        let start = std::time::Instant::now();
         let mut last = start.elapsed();
 
-        let seed_count = self.range.1 - self.range.0;
+        move |resolved| {
+            let elapsed = start.elapsed();
+            if elapsed - last >= std::time::Duration::from_millis(500) {
+                let percent = resolved as f64 * 100.0 / seed_count as f64;
+                println!("Worker #{id} [{elapsed:?}] resolved {percent:.2}%");
+                last = elapsed;
+            }
+        }
+    }
+
+    /// There is no clock to read the elapsed time from in a browser
+    #[cfg(target_arch = "wasm32")]
+    fn progress(&self) -> impl FnMut(usize) {
+        |_| {}
+    }
 
+    fn run(self) -> u64 {
+        println!(
+            "Worker #{} starts working for range ({}, {})",
+            self.id, self.range.0, self.range.1
+        );
+
+        let mut progress = self.progress();
         (self.range.0..self.range.1)
             .enumerate()
             .map(|(idx, s)| {
-                let elapsed = start.elapsed();
-                if elapsed - last >= std::time::Duration::from_millis(500) {
-                    let id = self.id;
-                    let percent = idx as f64 * 100.0 / seed_count as f64;
-                    println!("Worker #{id} [{elapsed:?}] resolved {percent:.2}%");
-                    last = elapsed;
-                }
-
+                progress(idx);
                 self.almanac.resolve(s, "seed", "location")
             })
             .min()
@@ -223,51 +459,540 @@ impl Worker {
     }
 }
 
+/// A section of the almanac, its lines between two blank lines: either the seeds or a map
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(super) struct Section(Vec<String>);
+
+impl FromStr for Section {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(s.lines().map(str::to_string).collect()))
+    }
+}
+
+/// Splits the almanac `lines` into its sections, the seeds coming first
+fn sections<S: AsRef<str>>(lines: &[S]) -> Vec<Section> {
+    let section = |lines: &mut dyn Iterator<Item = &S>| {
+        Section(lines.map(|line| line.as_ref().to_string()).collect())
+    };
+
+    let (header, blocks) = util::split_sections(lines);
+    std::iter::once(section(&mut header.into_iter()))
+        .chain(blocks.into_iter().map(|block| section(&mut block.iter())))
+        .collect()
+}
+
+fn parse_sections(sections: &[Section]) -> anyhow::Result<(Seeds, Almanac)> {
+    let (header, blocks) = sections.split_first().ok_or(anyhow!("missing seeds"))?;
+    let seeds = match header.0.as_slice() {
+        [seeds] => Seeds::from_str(seeds)?,
+        _ => bail!("expected a single line of seeds, got {}", header.0.len()),
+    };
+
+    if blocks.is_empty() {
+        bail!("missing blocks");
+    }
+
+    let blocks = blocks
+        .iter()
+        .map(|block| block.0.as_slice())
+        .collect::<Vec<_>>();
+    Ok((seeds, Almanac::create(&blocks)?))
+}
+
+#[cfg(test)]
+fn parse(lines: &[String]) -> anyhow::Result<(Seeds, Almanac)> {
+    parse_sections(&sections(lines))
+}
+
+/// Replaces the seeds of the almanac `sections` with the numbers of `seeds`
+fn replace_seeds(sections: &mut [Section], seeds: &str) -> anyhow::Result<()> {
+    let header = sections.first_mut().ok_or(anyhow!("missing seeds"))?;
+    header.0 = vec![format!("seeds: {}", seeds.trim())];
+
+    Ok(())
+}
+
+/// The seeds to plant, along with the almanac telling where to plant them
+pub struct Planting {
+    seeds: Seeds,
+    almanac: Almanac,
+}
+
+impl Planting {
+    /// The lowest location of the ranges of seeds, resolving every seed with a thread per range.
+    /// Kept to cross-check and benchmark `lowest_location_by_intervals`
+    pub fn lowest_location_per_seed(&self) -> Option<u64> {
+        let Planting { seeds, almanac } = self;
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let lowest_location = std::thread::scope(|s| {
+            let handles = Worker::for_ranges(seeds, almanac)
+                .into_iter()
+                .map(|w| s.spawn(|| w.run()))
+                .collect::<Vec<_>>();
+
+            handles.into_iter().map(|h| h.join().unwrap()).min()
+        });
+
+        // There are no threads to spawn in a browser
+        #[cfg(target_arch = "wasm32")]
+        let lowest_location = Worker::for_ranges(seeds, almanac)
+            .into_iter()
+            .map(Worker::run)
+            .min();
+
+        lowest_location
+    }
+
+    /// The lowest location of the ranges of seeds, mapping whole intervals of seeds
+    pub fn lowest_location_by_intervals(&self) -> Option<u64> {
+        self.almanac.lowest_location_of_ranges(self.seeds.ranges())
+    }
+}
+
+/// Parses the whole almanac
+impl FromStr for Planting {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from(sections(&s.lines().collect::<Vec<_>>()))
+    }
+}
+
+impl TryFrom<Vec<Section>> for Planting {
+    type Error = anyhow::Error;
+
+    fn try_from(sections: Vec<Section>) -> Result<Self, Self::Error> {
+        let (seeds, almanac) = parse_sections(&sections)?;
+        Ok(Self { seeds, almanac })
+    }
+}
+
 pub(super) struct Day5;
 impl super::day::Day for Day5 {
-    type Item = String;
+    type Item = Section;
     type Answer = u64;
+    type Context = Planting;
 
     const DAY: usize = 5;
+    /// The ends of the ranges of the maps used to be mapped
+    const REVISION: u32 = 1;
+    const WHOLE_FILE: bool = true;
 
-    fn part_1(lines: Vec<Self::Item>) -> anyhow::Result<Self::Answer> {
-        let mut lines = lines.into_iter();
-        let seeds = Seeds::from_str(lines.next().ok_or(anyhow!("missing seeds"))?.as_str())?;
+    fn parse_input(input: &str) -> anyhow::Result<Vec<Self::Item>> {
+        Ok(sections(&input.lines().collect::<Vec<_>>()))
+    }
 
-        lines.next().ok_or(anyhow!("missing blocks"))?;
+    fn with_options(
+        mut sections: Vec<Self::Item>,
+        options: &Options,
+    ) -> anyhow::Result<Vec<Self::Item>> {
+        if let Some(path) = &options.seeds {
+            let seeds = std::fs::read_to_string(path)
+                .map_err(|e| anyhow!("failed to read seeds from {}: {e}", path.display()))?;
+            replace_seeds(&mut sections, seeds.trim_start_matches('\u{feff}'))?;
+        }
 
-        let almanac = Almanac::create(lines.as_slice())?;
-        let lowest_location = seeds
-            .0
-            .iter()
-            .map(|seed| almanac.resolve(*seed, "seed", "location"))
-            .min()
+        Ok(sections)
+    }
+
+    fn part_1(planting: &Self::Context) -> anyhow::Result<Self::Answer> {
+        let Planting { seeds, almanac } = planting;
+        let lowest_location = almanac
+            .lowest_k_locations(seeds.0.iter().copied(), 1)
+            .first()
+            .copied()
             .ok_or(anyhow!("impossible to compute lowest location"))?;
 
         Ok(lowest_location)
     }
 
-    fn part_2(lines: Vec<Self::Item>) -> anyhow::Result<Self::Answer> {
-        let mut lines = lines.into_iter();
-        let seeds = Seeds::from_str(lines.next().ok_or(anyhow!("missing seeds"))?.as_str())?;
+    fn part_2(planting: &Self::Context) -> anyhow::Result<Self::Answer> {
+        let lowest_location = planting
+            .lowest_location_by_intervals()
+            .ok_or(anyhow!("impossible to compute lowest location"))?;
 
-        lines.next().ok_or(anyhow!("missing blocks"))?;
+        Ok(lowest_location)
+    }
+}
 
-        let almanac = Almanac::create(lines.as_slice())?;
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{day::Day, testing::Rng};
 
-        let lowest_location = std::thread::scope(|s| {
-            let workers = seeds.ranges().enumerate().map(|(idx, range)| Worker {
-                id: idx,
-                almanac: almanac.clone(),
-                range,
-            });
+    fn lines(s: &str) -> Vec<String> {
+        s.lines().map(str::to_string).collect()
+    }
 
-            let handles = workers.map(|w| s.spawn(|| w.run())).collect::<Vec<_>>();
+    /// seed 1 -> soil 1 -> location 1
+    /// seed 2 -> soil 10 -> location 10
+    /// seed 3 -> soil 11 -> location 0
+    /// seed 4 -> soil 12 -> location 1
+    /// seed 5 -> soil 5 -> location 5
+    const SMALL_ALMANAC: &str = "\
+seeds: 1 5
 
-            handles.into_iter().map(|h| h.join().unwrap()).min()
-        })
-        .ok_or(anyhow!("impossible to compute lowest location"))?;
+seed-to-soil map:
+10 2 3
 
-        Ok(lowest_location)
+soil-to-location map:
+0 11 2";
+
+    /// Two ranges touching at 15: `[10, 15)` to `[50, 55)` and `[15, 20)` to `[100, 105)`
+    fn touching_map() -> anyhow::Result<Map> {
+        Map::try_from(lines("seed-to-soil map:\n100 15 5\n50 10 5"))
+    }
+
+    /// Asserts that the intervals mapped from `[start, end)` cover as many values as the input,
+    /// each of them exactly once
+    fn assert_covers_once(mapped: &[(u64, u64)], start: u64, end: u64) {
+        let mut values = mapped
+            .iter()
+            .flat_map(|&(start, end)| start..end)
+            .collect::<Vec<_>>();
+        values.sort_unstable();
+        let len = values.len();
+        values.dedup();
+
+        assert_eq!(values.len(), len, "{mapped:?} overlap");
+        assert_eq!(len as u64, end - start, "{mapped:?} drop values");
+    }
+
+    #[test]
+    fn map_interval_across_touching_ranges() -> anyhow::Result<()> {
+        let map = touching_map()?;
+
+        // Starts mid-range and ends mid-next-range
+        let mapped = map.map_interval(12, 18);
+        assert_eq!(mapped, [(52, 55), (100, 103)]);
+        assert_covers_once(&mapped, 12, 18);
+
+        // Exactly the seam
+        assert_eq!(map.map_interval(14, 16), [(54, 55), (100, 101)]);
+        assert_eq!(map.map_interval(15, 16), [(100, 101)]);
+        assert_eq!(map.map_interval(14, 15), [(54, 55)]);
+
+        // Spans both ranges and the identity gaps around them
+        let mapped = map.map_interval(5, 25);
+        assert_eq!(mapped, [(5, 10), (50, 55), (100, 105), (20, 25)]);
+        assert_covers_once(&mapped, 5, 25);
+
+        assert_eq!(map.map_interval(0, 10), [(0, 10)]);
+        assert_eq!(map.map_interval(20, 30), [(20, 30)]);
+        assert!(map.map_interval(12, 12).is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn resolve_trace_of_sample_seed() -> anyhow::Result<()> {
+        let (_, almanac) = parse(&lines(&std::fs::read_to_string("src/day5/example.txt")?))?;
+
+        let trace = almanac.resolve_trace(79);
+        let expected = [
+            ("seed", 79),
+            ("soil", 81),
+            ("fertilizer", 81),
+            ("water", 81),
+            ("light", 74),
+            ("temperature", 78),
+            ("humidity", 78),
+            ("location", 82),
+        ];
+        assert_eq!(
+            trace,
+            expected.map(|(category, value)| (category.to_string(), value))
+        );
+        assert_eq!(
+            trace.last().map(|(_, value)| *value),
+            Some(almanac.resolve(79, "seed", "location"))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn seed_for_sample_locations() -> anyhow::Result<()> {
+        let sample = lines(&std::fs::read_to_string("src/day5/example.txt")?);
+        let (seeds, almanac) = parse(&sample)?;
+
+        for seed in seeds.0 {
+            let location = almanac.resolve(seed, "seed", "location");
+            let found = almanac
+                .seed_for_location(location)
+                .expect("a seed resolves to the location");
+            assert_eq!(almanac.resolve(found, "seed", "location"), location);
+        }
+        assert_eq!(almanac.seed_for_location(82), Some(79));
+
+        let (_, small) = parse(&lines(SMALL_ALMANAC))?;
+        // Seeds 0 and 3 both end up at location 0
+        assert_eq!(small.seed_for_location(0), Some(0));
+        // Seed 2 goes through soil 10, as does seed 10 which is outside of every range
+        assert_eq!(small.seed_for_location(10), Some(2));
+        // Soil 11 always moves to location 0
+        assert_eq!(small.seed_for_location(11), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn worker_ids_follow_range_starts() -> anyhow::Result<()> {
+        let (_, almanac) = parse(&lines(SMALL_ALMANAC))?;
+        let seeds = Seeds::from_str("seeds: 79 14 55 13 3 2")?;
+
+        let assignments = || {
+            Worker::for_ranges(&seeds, &almanac)
+                .into_iter()
+                .map(|w| (w.id, w.range))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(assignments(), [(0, (3, 5)), (1, (55, 68)), (2, (79, 93))]);
+        assert_eq!(assignments(), assignments());
+
+        Ok(())
+    }
+
+    #[test]
+    fn whole_file_sections() -> anyhow::Result<()> {
+        let input = std::fs::read_to_string("src/day5/example.txt")?;
+        let sections = Day5::parse_input(&input)?;
+        assert_eq!(sections.len(), 8);
+        assert_eq!(sections[0], "seeds: 79 14 55 13".parse()?);
+        assert_eq!(sections[1].0[0], "seed-to-soil map:");
+
+        assert_eq!(Day5::parse_input(&input.replace('\n', "\r\n"))?, sections);
+        assert!(Day5::build_context(Day5::parse_input("seeds: 79 14\n")?).is_err());
+
+        Ok(())
+    }
+
+    fn input_planting() -> anyhow::Result<Planting> {
+        let path = crate::input_path(5);
+        std::fs::read_to_string(&path)
+            .map_err(|e| anyhow!("failed to read {}: {e}", path.display()))?
+            .parse()
+    }
+
+    #[test]
+    #[ignore = "resolves every seed of the input"]
+    fn part_2_solvers_agree_on_the_input() -> anyhow::Result<()> {
+        let planting = input_planting()?;
+        assert_eq!(
+            planting.lowest_location_per_seed(),
+            planting.lowest_location_by_intervals()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn part_2_solvers_agree_on_the_sample() -> anyhow::Result<()> {
+        let planting = Day5::build_context(Day5::parse_input(&std::fs::read_to_string(
+            "src/day5/example.txt",
+        )?)?)?;
+        assert_eq!(planting.lowest_location_per_seed(), Some(46));
+        assert_eq!(planting.lowest_location_by_intervals(), Some(46));
+
+        Ok(())
+    }
+
+    #[test]
+    fn seeds_override() -> anyhow::Result<()> {
+        let sections = Day5::parse_input(&std::fs::read_to_string("src/day5/example.txt")?)?;
+        assert_eq!(Day5::part_1(&Day5::build_context(sections.clone())?)?, 35);
+
+        let options = Options {
+            seeds: Some("src/day5/example_seeds.txt".into()),
+            ..Options::default()
+        };
+        let overridden = Day5::with_options(sections.clone(), &options)?;
+        assert_eq!(overridden[0], "seeds: 14 55".parse()?);
+        assert_eq!(overridden[1..], sections[1..]);
+        assert_eq!(Day5::part_1(&Day5::build_context(overridden)?)?, 43);
+
+        let options = Options {
+            seeds: Some("src/day5/missing_seeds.txt".into()),
+            ..Options::default()
+        };
+        assert!(Day5::with_options(sections, &options).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn map_with_range_reports_matching_range() -> anyhow::Result<()> {
+        let map = Map::try_from(lines("seed-to-soil map:\n50 98 2\n52 50 48"))?;
+
+        let (mapped, range) = map.map_with_range(79).expect("79 is mapped");
+        assert_eq!(mapped, 81);
+        assert_eq!(
+            *range,
+            MapRange {
+                destination_start: 52,
+                source_start: 50,
+                len: 48,
+            }
+        );
+
+        assert_eq!(
+            map.map_with_range(99).map(|(_, r)| r.source_start),
+            Some(98)
+        );
+        assert!(map.map_with_range(10).is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn end_of_range_is_excluded() -> anyhow::Result<()> {
+        let range = "50 98 2".parse::<MapRange>()?;
+        assert_eq!(range.map(98), Some(50));
+        assert_eq!(range.map(99), Some(51));
+        assert_eq!(range.map(100), None);
+
+        // 98 is one past the end of the first range, and only belongs to the second one
+        let map = Map::try_from(lines("seed-to-soil map:\n52 50 48\n50 98 2"))?;
+        assert_eq!(map.map(97), Some(99));
+        assert_eq!(map.map(98), Some(50));
+        assert_eq!(map.map(100), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn lowest_k_locations_of_sample() -> anyhow::Result<()> {
+        let lines = lines(&std::fs::read_to_string("src/day5/example.txt")?);
+        let (seeds, almanac) = parse(&lines)?;
+
+        let seeds = seeds.0.iter().copied();
+        assert_eq!(
+            almanac.lowest_k_locations(seeds.clone(), 3),
+            vec![35, 43, 82]
+        );
+        assert_eq!(almanac.lowest_k_locations(seeds.clone(), 1), vec![35]);
+        assert_eq!(
+            almanac.lowest_k_locations(seeds.clone(), 10),
+            vec![35, 43, 82, 86]
+        );
+        assert!(almanac.lowest_k_locations(seeds, 0).is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn out_of_bounds_short_circuit_matches_full_scan() -> anyhow::Result<()> {
+        let map = Map::try_from(lines(
+            "light-to-temperature map:\n45 77 23\n81 45 19\n68 64 13",
+        ))?;
+        assert_eq!(map.bounds, (45, 100));
+
+        for n in 0..150 {
+            let full_scan = map.ranges.iter().find_map(|r| r.map(n));
+            assert_eq!(map.map(n), full_scan, "{n}");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn merge_overlapping_intervals() {
+        assert_eq!(
+            merge_intervals(vec![(10, 15), (0, 5), (3, 8), (15, 20), (30, 30)]),
+            [(0, 8), (10, 20)]
+        );
+        assert!(merge_intervals(Vec::new()).is_empty());
+    }
+
+    /// An almanac of `maps` categories of `ranges` random ranges each, from seeds to locations
+    fn random_almanac(rng: &mut Rng, maps: usize, ranges: usize) -> anyhow::Result<Almanac> {
+        let categories = (0..=maps)
+            .map(|idx| match idx {
+                0 => "seed".to_string(),
+                idx if idx == maps => "location".to_string(),
+                idx => format!("category{idx}"),
+            })
+            .collect::<Vec<_>>();
+
+        let blocks = categories
+            .windows(2)
+            .map(|w| {
+                std::iter::once(format!("{}-to-{} map:", w[0], w[1]))
+                    .chain((0..ranges).map(|_| {
+                        format!(
+                            "{} {} {}",
+                            rng.range(0, 1000),
+                            rng.range(0, 1000),
+                            rng.range(1, 100)
+                        )
+                    }))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        Almanac::create(&blocks.iter().map(Vec::as_slice).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn interval_solver_of_sample() -> anyhow::Result<()> {
+        let lines = lines(&std::fs::read_to_string("src/day5/example.txt")?);
+        let (seeds, almanac) = parse(&lines)?;
+
+        assert_eq!(almanac.lowest_location_of_ranges(seeds.ranges()), Some(46));
+        assert_eq!(almanac.lowest_location_of_ranges([(79, 80)]), Some(82));
+        assert_eq!(almanac.lowest_location_of_ranges([]), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn interval_solver_holds_few_intervals() -> anyhow::Result<()> {
+        let mut rng = Rng::new(5);
+        for _ in 0..20 {
+            let almanac = random_almanac(&mut rng, 7, 30)?;
+            let ranges = almanac.maps.iter().map(|m| m.ranges.len()).sum::<usize>();
+            let seeds = (0..10)
+                .map(|_| {
+                    let start = rng.range(0, 1000);
+                    (start, start + rng.range(1, 500))
+                })
+                .collect::<Vec<_>>();
+
+            // Every range splits at most one interval at each of its two boundaries
+            let (lowest, peak) = almanac.lowest_location_with_peak(seeds.iter().copied());
+            assert!(lowest.is_some());
+            assert!(
+                peak <= 1 + 2 * ranges,
+                "{peak} intervals for {ranges} ranges"
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn small_almanac_lowest_location_for_ranges() -> anyhow::Result<()> {
+        let (seeds, almanac) = parse(&lines(SMALL_ALMANAC))?;
+
+        let per_seed = seeds
+            .ranges()
+            .flat_map(|(start, end)| start..end)
+            .map(|seed| almanac.resolve(seed, "seed", "location"))
+            .min();
+        assert_eq!(per_seed, Some(0));
+
+        let workers = Worker::for_ranges(&seeds, &almanac)
+            .into_iter()
+            .map(Worker::run)
+            .min();
+        assert_eq!(workers, Some(0));
+
+        assert_eq!(almanac.lowest_location_of_ranges(seeds.ranges()), per_seed);
+
+        Ok(())
     }
 }
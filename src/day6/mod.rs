@@ -1,6 +1,8 @@
 use std::str::FromStr;
 
-use anyhow::anyhow;
+use anyhow::{anyhow, bail};
+
+use crate::{day::Options, util};
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 struct Millimeters(u64);
@@ -10,17 +12,54 @@ impl From<u64> for Millimeters {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+/// How a distance compares to the record to win the race
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+enum WinCondition {
+    /// Going further than the record, as in the puzzle
+    #[default]
+    Strict,
+    /// Tying the record is enough
+    Inclusive,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 struct Race {
     duration_ms: u64,
     distance: Millimeters,
 }
 
 impl Race {
+    #[cfg(test)]
     fn beats(&self, button_hold_duration_ms: u64) -> bool {
+        self.beats_with(button_hold_duration_ms, WinCondition::default())
+    }
+
+    #[cfg(test)]
+    fn beats_with(&self, button_hold_duration_ms: u64, condition: WinCondition) -> bool {
         let remaining_time = self.duration_ms - button_hold_duration_ms;
         let distance = button_hold_duration_ms * remaining_time;
-        distance > self.distance.0
+        match condition {
+            WinCondition::Strict => distance > self.distance.0,
+            WinCondition::Inclusive => distance >= self.distance.0,
+        }
+    }
+
+    /// How many button hold durations win the race. Holding for `t` beats the record when
+    /// `t * (duration - t) > distance`, that is strictly between the roots of
+    /// `t² - duration * t + distance`, and ties it on the roots themselves
+    fn winning_count(&self, condition: WinCondition) -> u64 {
+        let duration = self.duration_ms as f64;
+        let discriminant = duration * duration - 4.0 * self.distance.0 as f64;
+        if discriminant < 0.0 {
+            return 0;
+        }
+
+        let root = discriminant.sqrt();
+        let (lo, hi) = ((duration - root) / 2.0, (duration + root) / 2.0);
+        match condition {
+            WinCondition::Strict => util::count_integers_strictly_between(lo, hi),
+            WinCondition::Inclusive => util::count_integers_between(lo, hi),
+        }
     }
 }
 
@@ -44,84 +83,335 @@ impl FromStr for Number {
     }
 }
 
+/// Reads the `Time` and `Distance` lines, returning the remainder of both
+fn race_lines(lines: &[String]) -> anyhow::Result<(&str, &str)> {
+    let time = lines.first().ok_or(anyhow!("missing Time"))?;
+    let distance = lines.get(1).ok_or(anyhow!("missing Distance"))?;
+
+    Ok((
+        util::after_colon(util::strip_comment(time), "Time")?,
+        util::after_colon(util::strip_comment(distance), "Distance")?,
+    ))
+}
+
+/// The numbers of the line at `idx`, which must be labeled with `label`
+fn labeled_numbers(lines: &[String], idx: usize, label: &str) -> anyhow::Result<Vec<u64>> {
+    let line = lines.get(idx).ok_or(anyhow!("missing {label}"))?;
+    let (found, numbers) = util::parse_labeled_numbers(util::strip_comment(line))?;
+    if found != label {
+        bail!("expected `{label}`, got `{found}`");
+    }
+
+    Ok(numbers)
+}
+
+/// Parses each column of the input as a separate race
+fn parse_races(lines: &[String]) -> anyhow::Result<Vec<Race>> {
+    let times = labeled_numbers(lines, 0, "Time")?;
+    let distances = labeled_numbers(lines, 1, "Distance")?;
+
+    Ok(times
+        .into_iter()
+        .zip(distances)
+        .map(|(time, distance)| Race {
+            duration_ms: time,
+            distance: Millimeters::from(distance),
+        })
+        .collect())
+}
+
+/// Parses the input as a single race, ignoring the spaces between the digits
+fn parse_single_race(lines: &[String]) -> anyhow::Result<Race> {
+    let (time, distance) = race_lines(lines)?;
+
+    let time = Number::from_str(time)?;
+    let distance = Number::from_str(distance)?;
+
+    Ok(Race {
+        duration_ms: time.0,
+        distance: Millimeters(distance.0),
+    })
+}
+
+/// The sheet of the races, the whole input: a `Time` line followed by a `Distance` line
+#[derive(Debug, Clone)]
+pub(super) struct Sheet {
+    lines: Vec<String>,
+    /// How the races of the sheet are won, strictly beating their record unless told otherwise
+    condition: WinCondition,
+}
+
+impl FromStr for Sheet {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self {
+            lines: s.lines().map(str::to_string).collect(),
+            condition: WinCondition::default(),
+        })
+    }
+}
+
+impl TryFrom<Vec<Sheet>> for Sheet {
+    type Error = anyhow::Error;
+
+    fn try_from(sheets: Vec<Sheet>) -> Result<Self, Self::Error> {
+        let [sheet] = <[Sheet; 1]>::try_from(sheets)
+            .map_err(|sheets| anyhow!("expected a single sheet, got {}", sheets.len()))?;
+        Ok(sheet)
+    }
+}
+
+/// How many ways there are to win each race
+fn win_counts(races: &[Race], condition: WinCondition) -> Vec<u64> {
+    races.iter().map(|r| r.winning_count(condition)).collect()
+}
+
+fn ways_to_win(races: &[Race], condition: WinCondition) -> u64 {
+    win_counts(races, condition).into_iter().product()
+}
+
 pub(super) struct Day6;
 impl super::day::Day for Day6 {
-    type Item = String;
-    type Answer = usize;
+    type Item = Sheet;
+    type Answer = u64;
+    type Context = Sheet;
 
     const DAY: usize = 6;
+    const WHOLE_FILE: bool = true;
+    const UNIT: &'static str = "ways";
 
-    fn part_1(items: Vec<Self::Item>) -> anyhow::Result<Self::Answer> {
-        let time = items.get(0).ok_or(anyhow!("missing Time"))?;
-        let (_, times) = time.split_once(":").ok_or(anyhow!("missing Time"))?;
-        let times = times
-            .trim()
-            .split(" ")
-            .filter(|d| !d.is_empty())
-            .map(|t| t.parse());
-
-        let distance = items.get(1).ok_or(anyhow!("missing Distance"))?;
-        let (_, distances) = distance
-            .split_once(":")
-            .ok_or(anyhow!("missing Distance"))?;
-        let distances = distances
-            .trim()
-            .split(" ")
-            .filter(|d| !d.is_empty())
-            .map(|t| t.parse::<u64>());
-
-        let races = times
-            .zip(distances)
-            .map(|(time, distance)| {
-                let time = time?;
-                let distance = distance?;
-
-                Ok::<_, anyhow::Error>(Race {
-                    duration_ms: time,
-                    distance: Millimeters::from(distance),
-                })
-            })
-            .collect::<Result<Vec<_>, _>>()?;
+    fn parse_input(input: &str) -> anyhow::Result<Vec<Self::Item>> {
+        Ok(vec![input.parse()?])
+    }
+
+    fn with_options(
+        mut sheets: Vec<Self::Item>,
+        options: &Options,
+    ) -> anyhow::Result<Vec<Self::Item>> {
+        if options.tie_wins {
+            for sheet in &mut sheets {
+                sheet.condition = WinCondition::Inclusive;
+            }
+        }
+
+        Ok(sheets)
+    }
+
+    fn part_1(sheet: &Self::Context) -> anyhow::Result<Self::Answer> {
+        let races = parse_races(&sheet.lines)?;
+        Ok(ways_to_win(&races, sheet.condition))
+    }
+
+    fn part_2(sheet: &Self::Context) -> anyhow::Result<Self::Answer> {
+        let race = parse_single_race(&sheet.lines)?;
+        Ok(race.winning_count(sheet.condition))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::Rng;
+
+    fn sample() -> anyhow::Result<Vec<String>> {
+        Ok(std::fs::read_to_string("src/day6/example.txt")?
+            .lines()
+            .map(str::to_string)
+            .collect())
+    }
+
+    fn race(duration_ms: u64, distance: u64) -> Race {
+        Race {
+            duration_ms,
+            distance: Millimeters(distance),
+        }
+    }
+
+    #[test]
+    fn parse_sample_races() -> anyhow::Result<()> {
+        let races = parse_races(&sample()?)?;
+        assert_eq!(races, [race(7, 9), race(15, 40), race(30, 200)]);
+        assert_eq!(win_counts(&races, WinCondition::Strict), [4, 8, 9]);
+        assert_eq!(ways_to_win(&races, WinCondition::Strict), 288);
 
-        let answer = races
-            .into_iter()
-            .map(|r| (1..r.duration_ms - 1).filter(|d| r.beats(*d)).count())
-            .product();
-
-        Ok(answer)
-    }
-
-    fn part_2(items: Vec<Self::Item>) -> anyhow::Result<Self::Answer> {
-        let time = items.get(0).ok_or(anyhow!("missing Time"))?;
-        let (_, time) = time.split_once(":").ok_or(anyhow!("missing Time"))?;
-
-        let distance = items.get(1).ok_or(anyhow!("missing Distance"))?;
-        let (_, distance) = distance
-            .split_once(":")
-            .ok_or(anyhow!("missing Distance"))?;
-
-        let time = Number::from_str(time)?;
-        let distance = Number::from_str(distance)?;
-
-        let race = Race {
-            duration_ms: time
-                .0
-                .try_into()
-                .map_err(|_| anyhow!("duration too long"))?,
-            distance: Millimeters(
-                distance
-                    .0
-                    .try_into()
-                    .map_err(|_| anyhow!("distance too long"))?,
-            ),
+        Ok(())
+    }
+
+    #[test]
+    fn parse_sample_single_race() -> anyhow::Result<()> {
+        let race = parse_single_race(&sample()?)?;
+        assert_eq!(race, self::race(71530, 940200));
+        assert_eq!(race.winning_count(WinCondition::Strict), 71503);
+
+        Ok(())
+    }
+
+    #[test]
+    fn winning_count_matches_brute_force() {
+        for duration in 0..40 {
+            for distance in 0..duration * duration / 4 + 2 {
+                let race = race(duration, distance);
+                for condition in [WinCondition::Strict, WinCondition::Inclusive] {
+                    let brute_force = (0..=duration)
+                        .filter(|t| race.beats_with(*t, condition))
+                        .count() as u64;
+                    assert_eq!(
+                        race.winning_count(condition),
+                        brute_force,
+                        "{race:?} {condition:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    /// A record between zero and a bit above the best distance reachable in `duration`
+    fn random_race(rng: &mut Rng, duration: u64) -> Race {
+        let best = duration / 2 * (duration - duration / 2);
+        let distance = match rng.range(0, 4) {
+            0 => best,
+            1 => best.saturating_sub(rng.range(0, 3)),
+            _ => rng.range(0, best + 2),
         };
+        race(duration, distance)
+    }
+
+    /// The first hold duration beating the record, found by bisecting the first half of the race
+    /// where the distance increases with the hold duration
+    fn first_winning_hold(race: &Race) -> Option<u64> {
+        let (mut lo, mut hi) = (0, race.duration_ms / 2);
+        if !race.beats(hi) {
+            return None;
+        }
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if race.beats(mid) {
+                hi = mid;
+            } else {
+                lo = mid + 1;
+            }
+        }
+        Some(lo)
+    }
+
+    #[test]
+    fn winning_count_matches_brute_force_on_random_races() {
+        /// The longest race whose every hold duration is tried
+        const MAX_BRUTE_FORCE_DURATION: u64 = 10_000;
+
+        let mut rng = Rng::new(6);
+        for _ in 0..300 {
+            let duration = match rng.range(0, 3) {
+                0 => rng.range(0, 10),
+                _ => rng.range(10, MAX_BRUTE_FORCE_DURATION),
+            };
+            let race = random_race(&mut rng, duration);
+            let brute_force = (0..=duration).filter(|t| race.beats(*t)).count() as u64;
+            assert_eq!(
+                race.winning_count(WinCondition::Strict),
+                brute_force,
+                "{race:?}"
+            );
+        }
 
-        const MIN_BUTTON_HOLD_TIME_MS: u64 = 14;
-        let max_button_hold_time_ms = race.duration_ms - MIN_BUTTON_HOLD_TIME_MS;
+        // Too long to try every hold duration, the winning ones are symmetric around the middle
+        for _ in 0..300 {
+            let duration = rng.range(MAX_BRUTE_FORCE_DURATION, 100_000_000);
+            let race = random_race(&mut rng, duration);
+            let expected = first_winning_hold(&race).map_or(0, |first| duration - 2 * first + 1);
+            assert_eq!(
+                race.winning_count(WinCondition::Strict),
+                expected,
+                "{race:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn part_2_wins_from_any_hold_duration() -> anyhow::Result<()> {
+        use crate::day::Day;
+
+        // Every hold from 2 ms to 8 ms beats the record, far below the 14 ms of some inputs
+        let lines = ["Time: 1 0".to_string(), "Distance: 9".to_string()];
+        assert_eq!(first_winning_hold(&parse_single_race(&lines)?), Some(2));
+        assert_eq!(Day6::part_2(&lines.join("\n").parse()?)?, 7);
+
+        // Only holding for the whole first half wins
+        let lines = ["Time: 4 0".to_string(), "Distance: 399".to_string()];
+        assert_eq!(first_winning_hold(&parse_single_race(&lines)?), Some(20));
+        assert_eq!(Day6::part_2(&lines.join("\n").parse()?)?, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn tying_the_record_wins_only_when_inclusive() {
+        // Holding for 3 ms out of 7 goes 12 mm, exactly the record
+        let race = race(7, 12);
+        assert!(!race.beats_with(3, WinCondition::Strict));
+        assert!(race.beats_with(3, WinCondition::Inclusive));
+        assert!(!race.beats(3));
+        assert!(race.beats_with(4, WinCondition::Inclusive));
+        assert!(!race.beats_with(2, WinCondition::Inclusive));
+
+        // Both holds of 3 ms and 4 ms tie the record, none beats it
+        assert_eq!(race.winning_count(WinCondition::Strict), 0);
+        assert_eq!(race.winning_count(WinCondition::Inclusive), 2);
+    }
+
+    #[test]
+    fn ties_win_with_the_option() -> anyhow::Result<()> {
+        use crate::day::Day;
+
+        // Holding for 10 ms or 20 ms ties the record of the last race, which now counts
+        let options = Options {
+            tie_wins: true,
+            ..Options::default()
+        };
+        let sheets = Day6::parse_input(&std::fs::read_to_string("src/day6/example.txt")?)?;
+        let sheet = Day6::build_context(Day6::with_options(sheets, &options)?)?;
+        assert_eq!(Day6::part_1(&sheet)?, 4 * 8 * (9 + 2));
+
+        Ok(())
+    }
+
+    #[test]
+    fn comments_are_ignored() -> anyhow::Result<()> {
+        let lines = [
+            "Time:      7  15   30 # in ms".to_string(),
+            "Distance:  9  40  200 # in mm".to_string(),
+        ];
+        assert_eq!(
+            parse_races(&lines)?,
+            [race(7, 9), race(15, 40), race(30, 200)]
+        );
+        assert_eq!(parse_single_race(&lines)?, race(71530, 940200));
+
+        Ok(())
+    }
+
+    #[test]
+    fn whole_input_is_a_single_sheet() -> anyhow::Result<()> {
+        use crate::day::Day;
+
+        let sheets = Day6::parse_input(&std::fs::read_to_string("src/day6/example.txt")?)?;
+        assert_eq!(sheets.len(), 1);
+        assert_eq!(sheets[0].lines, sample()?);
+
+        let sheet = Day6::build_context(sheets)?;
+        assert_eq!(Day6::part_1(&sheet)?, 288);
+        assert_eq!(Day6::part_2(&sheet)?, 71503);
+
+        assert!(Day6::build_context(Vec::new()).is_err());
+
+        Ok(())
+    }
 
-        let answer = (MIN_BUTTON_HOLD_TIME_MS..=max_button_hold_time_ms)
-            .filter(|d| race.beats(*d))
-            .count();
-        Ok(answer)
+    #[test]
+    fn missing_distance_is_an_error() {
+        let lines = ["Time: 7 15 30".to_string()];
+        assert!(parse_races(&lines).is_err());
+        assert!(parse_single_race(&lines).is_err());
     }
 }
@@ -1,5 +1,13 @@
-use anyhow::anyhow;
-use std::{cmp::Ordering, collections::HashMap, marker::PhantomData, str::FromStr};
+use anyhow::{anyhow, bail};
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    fmt::{self, Display},
+    marker::PhantomData,
+    str::FromStr,
+};
+
+use crate::{day::Options, util};
 
 mod permutation;
 
@@ -27,7 +35,33 @@ impl Ord for Outcome {
 }
 
 impl Outcome {
-    fn value(&self) -> u8 {
+    /// Every outcome, from the weakest to the strongest
+    #[allow(dead_code)]
+    pub(super) const ALL: [Outcome; 7] = [
+        Outcome::HighCard,
+        Outcome::Pair,
+        Outcome::TwoPair,
+        Outcome::Set,
+        Outcome::FullHouse,
+        Outcome::FourOfAKind,
+        Outcome::FiveOfAKind,
+    ];
+
+    /// The outcome of a hand whose cards make groups of the given sizes, largest first
+    fn of_groups(groups: &[usize]) -> Outcome {
+        match groups {
+            [5] => Outcome::FiveOfAKind,
+            [4, ..] => Outcome::FourOfAKind,
+            [3, 2] => Outcome::FullHouse,
+            [3, ..] => Outcome::Set,
+            [2, 2, ..] => Outcome::TwoPair,
+            [2, ..] => Outcome::Pair,
+            _ => Outcome::HighCard,
+        }
+    }
+
+    /// The rank of the outcome, from 1 for the weakest to 7 for the strongest
+    pub(super) fn value(&self) -> u8 {
         match self {
             Outcome::HighCard => 1,
             Outcome::Pair => 2,
@@ -56,11 +90,42 @@ pub(super) enum Card {
     As,
 }
 
-impl TryFrom<char> for Card {
-    type Error = anyhow::Error;
+impl Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let c = match self {
+            Card::N(10) => 'T',
+            Card::N(n) => char::from_digit(*n as u32, 10).unwrap_or('?'),
+            Card::Jack => 'J',
+            Card::Queen => 'Q',
+            Card::King => 'K',
+            Card::As => 'A',
+        };
+        write!(f, "{c}")
+    }
+}
 
-    fn try_from(value: char) -> Result<Self, Self::Error> {
+impl Card {
+    /// Every card, from the weakest to the strongest when jacks are not jokers
+    pub(super) const ALL: [Card; 13] = [
+        Card::N(2),
+        Card::N(3),
+        Card::N(4),
+        Card::N(5),
+        Card::N(6),
+        Card::N(7),
+        Card::N(8),
+        Card::N(9),
+        Card::N(10),
+        Card::Jack,
+        Card::Queen,
+        Card::King,
+        Card::As,
+    ];
+
+    /// Parses a card. When `lenient`, `1` is also accepted as a ten, as some notations write it
+    fn from_char(value: char, lenient: bool) -> anyhow::Result<Self> {
         Ok(match value {
+            '1' if lenient => Card::N(10),
             '2' => Card::N(2),
             '3' => Card::N(3),
             '4' => Card::N(4),
@@ -74,11 +139,19 @@ impl TryFrom<char> for Card {
             'Q' => Card::Queen,
             'K' => Card::King,
             'A' => Card::As,
-            _ => Err(anyhow!("invalid card"))?,
+            _ => bail!("invalid card '{value}'"),
         })
     }
 }
 
+impl TryFrom<char> for Card {
+    type Error = anyhow::Error;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        Card::from_char(value, false)
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub(super) struct Hand<R: Rules> {
     cards: [Card; 5],
@@ -93,7 +166,7 @@ impl<R: Rules + Eq> PartialOrd for Hand<R> {
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
-struct Part1;
+pub(super) struct Part1;
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 struct Part2;
 
@@ -144,7 +217,7 @@ impl Rules for Part1 {
             return Outcome::FiveOfAKind;
         }
 
-        return Outcome::HighCard;
+        Outcome::HighCard
     }
 }
 
@@ -159,45 +232,79 @@ impl Rules for Part2 {
         }
     }
 
+    /// Jokers are best turned into the card the hand already holds the most of, which makes the
+    /// largest group of cards larger
     fn outcome(cards: &[Card; 5]) -> Outcome {
-        let jokers = cards
-            .iter()
-            .enumerate()
-            .filter_map(|(idx, c)| (*c == Card::Jack).then_some(idx))
-            .collect::<Vec<_>>();
+        let mut counts = HashMap::new();
+        for card in cards.iter().filter(|c| **c != Card::Jack) {
+            *counts.entry(card).or_insert(0usize) += 1;
+        }
 
-        const POSSIBLE_CARDS: &'static [Card] = &[
-            Card::N(2),
-            Card::N(3),
-            Card::N(4),
-            Card::N(5),
-            Card::N(6),
-            Card::N(7),
-            Card::N(8),
-            Card::N(9),
-            Card::N(10),
-            Card::Jack,
-            Card::Queen,
-            Card::King,
-            Card::As,
-        ];
+        let mut groups = counts.into_values().collect::<Vec<_>>();
+        groups.sort_unstable_by(|a, b| b.cmp(a));
+
+        let jokers = cards.len() - groups.iter().sum::<usize>();
+        match groups.first_mut() {
+            Some(largest) => *largest += jokers,
+            None => groups.push(jokers),
+        }
 
-        let combinations =
-            permutation::PermutationsWithReplacement::new(POSSIBLE_CARDS.iter(), jokers.len());
+        Outcome::of_groups(&groups)
+    }
+}
 
-        let possible_cards = combinations.map(|combination| {
-            let mut cards = cards.clone();
+/// Every hand made of `cards` with the cards at `positions` replaced by any card
+fn substitutions(cards: [Card; 5], positions: Vec<usize>) -> impl Iterator<Item = [Card; 5]> {
+    permutation::PermutationsWithReplacement::new(Card::ALL.iter(), positions.len()).map(
+        move |combination| {
+            let mut cards = cards;
 
-            for (joker_idx, card) in jokers.iter().zip(combination) {
-                cards[*joker_idx] = *card;
+            for (idx, card) in positions.iter().zip(combination) {
+                cards[*idx] = *card;
             }
 
             cards
-        });
+        },
+    )
+}
 
-        let outcome = possible_cards.map(|cards| Part1::outcome(&cards)).max();
-        outcome.unwrap()
-    }
+/// The card written as a placeholder for an unknown card, when analysing partial hands
+const UNKNOWN_CARD: char = 'X';
+
+/// Parses the five cards of a partial hand, where unknown cards are written `X`
+#[allow(dead_code)]
+fn parse_partial_cards(s: &str) -> anyhow::Result<[Option<Card>; 5]> {
+    let cards = s
+        .chars()
+        .map(|c| match c {
+            UNKNOWN_CARD => Ok(None),
+            c => Card::try_from(c).map(Some),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    cards
+        .try_into()
+        .map_err(|_| anyhow!("invalid number of cards in '{s}'"))
+}
+
+/// Every outcome a partial hand can reach under the given rules, whatever its unknown cards turn out
+/// to be, from the weakest to the strongest
+#[allow(dead_code)]
+fn possible_outcomes<R: Rules>(cards: &[Option<Card>; 5]) -> Vec<Outcome> {
+    let unknowns = cards
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, c)| c.is_none().then_some(idx))
+        .collect::<Vec<_>>();
+    // The unknown cards are all substituted, whatever they are set to here
+    let known = cards.map(|c| c.unwrap_or(Card::As));
+
+    let mut outcomes = substitutions(known, unknowns)
+        .map(|cards| R::outcome(&cards))
+        .collect::<Vec<_>>();
+    outcomes.sort();
+    outcomes.dedup();
+    outcomes
 }
 
 impl<R: Rules + Eq> Ord for Hand<R> {
@@ -221,6 +328,15 @@ impl<R: Rules + Eq> Ord for Hand<R> {
 }
 
 impl<R: Rules> Hand<R> {
+    /// The same hand, played under other rules
+    fn with_rules<S: Rules>(&self) -> Hand<S> {
+        Hand {
+            cards: self.cards,
+            bid: self.bid,
+            _phantom: PhantomData,
+        }
+    }
+
     fn outcome(&self) -> Outcome {
         R::outcome(&self.cards)
     }
@@ -230,15 +346,31 @@ impl<R: Rules> Hand<R> {
     }
 }
 
-impl<R: Rules> FromStr for Hand<R> {
-    type Err = anyhow::Error;
+impl<R: Rules> Display for Hand<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for card in &self.cards {
+            write!(f, "{card}")?;
+        }
+        write!(f, " {}", self.bid)
+    }
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (cards, bid) = s.split_once(" ").ok_or(anyhow!("invalid hand"))?;
+impl<R: Rules> Hand<R> {
+    /// Parses a hand, accepting `1` for ten cards when `lenient`. The cards and the bid may be
+    /// padded with spaces, as in column-aligned files
+    fn parse(s: &str, lenient: bool) -> anyhow::Result<Self> {
+        let (cards, bid) = s
+            .trim()
+            .split_once(char::is_whitespace)
+            .ok_or(anyhow!("invalid hand"))?;
+        let bid = bid.trim_start();
 
         let cards = cards
             .chars()
-            .map(Card::try_from)
+            .enumerate()
+            .map(|(idx, c)| {
+                Card::from_char(c, lenient).map_err(|e| anyhow!("{e} at index {idx} in '{s}'"))
+            })
             .collect::<Result<Vec<_>, _>>()?;
 
         let bid = bid.parse()?;
@@ -246,21 +378,90 @@ impl<R: Rules> FromStr for Hand<R> {
         Ok(Self {
             cards: cards
                 .try_into()
-                .map_err(|_| anyhow!("invalid number of cards"))?,
+                .map_err(|cards: Vec<_>| anyhow!("expected 5 cards, got {}", cards.len()))?,
             bid,
             _phantom: PhantomData,
         })
     }
 }
 
-fn solve<R: Rules + Eq>(items: Vec<String>) -> anyhow::Result<u64> {
-    let mut hands = items
-        .into_iter()
-        .map(|s| s.parse::<Hand<R>>())
-        .collect::<Result<Vec<_>, _>>()?;
+impl<R: Rules> FromStr for Hand<R> {
+    type Err = anyhow::Error;
 
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Hand::parse(s, false)
+    }
+}
+
+fn parse_hands<R: Rules>(items: &[String]) -> anyhow::Result<Vec<Hand<R>>> {
+    items.iter().map(|s| s.parse::<Hand<R>>()).collect()
+}
+
+/// Parses the hands and sorts them from the weakest to the strongest
+fn sorted_hands<R: Rules + Eq>(items: &[String]) -> anyhow::Result<Vec<Hand<R>>> {
+    let mut hands = parse_hands(items)?;
+    hands.sort();
+    Ok(hands)
+}
+
+/// The hands ranked under the given rules, one aligned row per hand from the weakest to the
+/// strongest
+#[allow(dead_code)]
+fn leaderboard<R: Rules + Eq>(items: &[String]) -> anyhow::Result<String> {
+    const HEADER: [&str; 5] = ["rank", "hand", "outcome", "bid", "score"];
+
+    let rows = sorted_hands::<R>(items)?
+        .iter()
+        .enumerate()
+        .map(|(idx, hand)| {
+            let rank = idx + 1;
+            [
+                rank.to_string(),
+                hand.cards.iter().map(Card::to_string).collect(),
+                format!("{:?}", hand.outcome()),
+                hand.bid.to_string(),
+                hand.score(rank).to_string(),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    let mut widths = HEADER.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let line = |cells: [&str; 5]| {
+        // Numbers are aligned to the right, text to the left
+        let [rank, hand, outcome, bid, score] = cells;
+        let [w_rank, w_hand, w_outcome, w_bid, w_score] = widths;
+        format!("{rank:>w_rank$} | {hand:<w_hand$} | {outcome:<w_outcome$} | {bid:>w_bid$} | {score:>w_score$}\n")
+    };
+
+    let mut board = line(HEADER);
+    for row in &rows {
+        board.push_str(&line(row.each_ref().map(String::as_str)));
+    }
+
+    Ok(board)
+}
+
+/// Solves with the given rules. The sort being stable, hands that are equal keep their input order,
+/// the first one getting the lowest rank, unless `strict` is set, in which case they are rejected
+/// as their ranking would be ambiguous
+fn solve<R: Rules + Eq>(mut hands: Vec<Hand<R>>, strict: bool) -> anyhow::Result<u64> {
     hands.sort();
 
+    if strict {
+        if let Some(tie) = hands
+            .windows(2)
+            .find(|w| w[0].cmp(&w[1]) == Ordering::Equal)
+        {
+            bail!("hands `{}` and `{}` are tied", tie[0], tie[1]);
+        }
+    }
+
     let answer = hands
         .into_iter()
         .enumerate()
@@ -270,18 +471,497 @@ fn solve<R: Rules + Eq>(items: Vec<String>) -> anyhow::Result<u64> {
     Ok(answer)
 }
 
+/// The total winnings of the hands of `lines` ranked under the given rules, ties keeping their input
+/// order
+#[allow(dead_code)]
+pub(super) fn total_winnings<R: Rules + Eq>(lines: &[String]) -> anyhow::Result<u64> {
+    solve::<R>(parse_hands(lines)?, false)
+}
+
+/// How many hands of each outcome there are under the given rules, from the weakest outcome to the
+/// strongest
+#[allow(dead_code)]
+fn outcome_histogram<R: Rules>(items: &[String]) -> anyhow::Result<Vec<(Outcome, usize)>> {
+    // The outcome only depends on the multiset of the cards, not on their order
+    let mut outcome = util::memoize(|cards: &[Card; 5]| R::outcome(cards));
+    let outcomes = items
+        .iter()
+        .map(|s| {
+            let mut cards = s.parse::<Hand<R>>()?.cards;
+            cards.sort_by_key(R::card_value);
+            Ok::<_, anyhow::Error>(outcome(&cards))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Outcome::ALL
+        .iter()
+        .map(|&outcome| (outcome, outcomes.iter().filter(|&&o| o == outcome).count()))
+        .collect())
+}
+
+/// A line of the input, holding a hand, along with how the hands are read and ranked
+#[derive(Debug, Clone)]
+pub(super) struct Line {
+    text: String,
+    /// `1` is accepted for the ten cards of the hand
+    lenient: bool,
+    /// Tied hands are rejected instead of keeping their input order
+    strict: bool,
+}
+
+impl FromStr for Line {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self {
+            text: s.to_string(),
+            lenient: false,
+            strict: false,
+        })
+    }
+}
+
+/// The hands of the input, parsed once for both parts
+pub(super) struct Hands {
+    hands: Vec<Hand<Part1>>,
+    strict: bool,
+}
+
+impl TryFrom<Vec<Line>> for Hands {
+    type Error = anyhow::Error;
+
+    fn try_from(lines: Vec<Line>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            hands: lines
+                .iter()
+                .map(|line| Hand::parse(&line.text, line.lenient))
+                .collect::<anyhow::Result<_>>()?,
+            strict: lines.iter().any(|line| line.strict),
+        })
+    }
+}
+
+impl Hands {
+    fn with_rules<R: Rules>(&self) -> Vec<Hand<R>> {
+        self.hands.iter().map(Hand::with_rules).collect()
+    }
+}
+
+/// Jokers only ever improve the outcome of a hand, but they also become the weakest card, so hands
+/// holding some may lose ties they used to win. The total of part 2 can thus be lower than the total
+/// of part 1, as it is on the sample, and no invariant between the answers is checked
 pub(super) struct Day7;
 impl super::day::Day for Day7 {
-    type Item = String;
+    type Item = Line;
     type Answer = u64;
+    type Context = Hands;
 
     const DAY: usize = 7;
 
-    fn part_1(items: Vec<Self::Item>) -> anyhow::Result<Self::Answer> {
-        solve::<Part1>(items)
+    fn with_options(
+        mut lines: Vec<Self::Item>,
+        options: &Options,
+    ) -> anyhow::Result<Vec<Self::Item>> {
+        for line in &mut lines {
+            line.lenient = options.lenient_cards;
+            line.strict = options.strict_ties;
+        }
+
+        Ok(lines)
+    }
+
+    fn validate_items(items: &[Self::Item], _options: &Options) -> anyhow::Result<()> {
+        if items.is_empty() {
+            bail!("no hands, is this the right input?");
+        }
+
+        Ok(())
+    }
+
+    fn part_1(hands: &Self::Context) -> anyhow::Result<Self::Answer> {
+        solve::<Part1>(hands.with_rules(), hands.strict)
+    }
+
+    fn part_2(hands: &Self::Context) -> anyhow::Result<Self::Answer> {
+        solve::<Part2>(hands.with_rules(), hands.strict)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::day::Day;
+
+    fn solve_lines<R: Rules + Eq>(items: Vec<String>, strict: bool) -> anyhow::Result<u64> {
+        solve::<R>(parse_hands(&items)?, strict)
+    }
+
+    /// Ranks hands by their cards alone, as if every hand was a high card
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    struct CardsOnly;
+
+    impl Rules for CardsOnly {
+        fn outcome(_cards: &[Card; 5]) -> Outcome {
+            Outcome::HighCard
+        }
+
+        fn card_value(card: &Card) -> u8 {
+            Part1::card_value(card)
+        }
+    }
+
+    #[test]
+    fn total_winnings_with_custom_rules() -> anyhow::Result<()> {
+        let lines = std::fs::read_to_string("src/day7/example.txt")?
+            .lines()
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(total_winnings::<Part1>(&lines)?, 6440);
+        assert_eq!(total_winnings::<Part2>(&lines)?, 5905);
+
+        // 32T3K < T55J5 < QQQJA < KTJJT < KK677
+        assert_eq!(
+            total_winnings::<CardsOnly>(&lines)?,
+            765 + 684 * 2 + 483 * 3 + 220 * 4 + 28 * 5
+        );
+
+        Ok(())
+    }
+
+    /// Asserts that the rules order every pair of cards as their expected ranks do
+    fn assert_card_order<R: Rules>(rank: impl Fn(usize, &Card) -> usize) {
+        for (i, a) in Card::ALL.iter().enumerate() {
+            for (j, b) in Card::ALL.iter().enumerate() {
+                assert_eq!(
+                    R::card_value(a).cmp(&R::card_value(b)),
+                    rank(i, a).cmp(&rank(j, b)),
+                    "{a} vs {b}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn card_order_matrix() {
+        assert_card_order::<Part1>(|idx, _| idx);
+        // Jokers are weaker than any other card
+        assert_card_order::<Part2>(|idx, card| if *card == Card::Jack { 0 } else { idx + 1 });
+
+        assert!(Part1::card_value(&Card::Jack) > Part1::card_value(&Card::N(2)));
+        assert!(Part2::card_value(&Card::Jack) < Part2::card_value(&Card::N(2)));
+    }
+
+    /// Every multiset of five cards, as indexes into `Card::ALL`
+    fn multisets() -> impl Iterator<Item = [usize; 5]> {
+        let n = Card::ALL.len();
+        (0..n).flat_map(move |a| {
+            (a..n).flat_map(move |b| {
+                (b..n).flat_map(move |c| {
+                    (c..n).flat_map(move |d| (d..n).map(move |e| [a, b, c, d, e]))
+                })
+            })
+        })
+    }
+
+    /// The group sizes of a hand, largest first
+    fn shape(cards: &[Card; 5]) -> Vec<usize> {
+        let mut counts = HashMap::new();
+        for card in cards {
+            *counts.entry(card).or_insert(0usize) += 1;
+        }
+
+        let mut shape = counts.into_values().collect::<Vec<_>>();
+        shape.sort_unstable_by(|a, b| b.cmp(a));
+        shape
+    }
+
+    #[test]
+    fn outcome_values_increase() {
+        let values = Outcome::ALL.map(|o| o.value());
+        assert_eq!(values, [1, 2, 3, 4, 5, 6, 7]);
+        assert!(Outcome::ALL.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn invalid_card_is_located() {
+        let err = "AKQXT 10".parse::<Hand<Part1>>().unwrap_err();
+        assert_eq!(err.to_string(), "invalid card 'X' at index 3 in 'AKQXT 10'");
+
+        let err = "xKQJT 10".parse::<Hand<Part1>>().unwrap_err();
+        assert_eq!(err.to_string(), "invalid card 'x' at index 0 in 'xKQJT 10'");
+
+        assert_eq!(
+            Card::try_from('1').unwrap_err().to_string(),
+            "invalid card '1'"
+        );
+    }
+
+    #[test]
+    fn possible_outcomes_of_partial_hands() -> anyhow::Result<()> {
+        let outcomes = possible_outcomes::<Part1>(&parse_partial_cards("AAXXX")?);
+        assert_eq!(outcomes, Outcome::ALL[1..]);
+        assert!(outcomes.contains(&Outcome::FiveOfAKind));
+
+        assert_eq!(
+            possible_outcomes::<Part1>(&parse_partial_cards("AKQJT")?),
+            [Outcome::HighCard]
+        );
+        assert_eq!(
+            possible_outcomes::<Part2>(&parse_partial_cards("AKQJX")?),
+            [Outcome::Pair, Outcome::Set]
+        );
+
+        assert!(parse_partial_cards("AAXX").is_err());
+        assert!(parse_partial_cards("AAXXY").is_err());
+        assert!("AAXXX 10".parse::<Hand<Part1>>().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn aligned_hands() -> anyhow::Result<()> {
+        let hand = "  AKQJT  123".parse::<Hand<Part1>>()?;
+        assert_eq!(
+            hand.cards,
+            [Card::As, Card::King, Card::Queen, Card::Jack, Card::N(10)]
+        );
+        assert_eq!(hand.bid, 123);
+        assert_eq!("\tAKQJT\t123 ".parse::<Hand<Part1>>()?, hand);
+
+        let err = "  AKQJ  123".parse::<Hand<Part1>>().unwrap_err();
+        assert_eq!(err.to_string(), "expected 5 cards, got 4");
+        assert!("AKQJTA 123".parse::<Hand<Part1>>().is_err());
+        assert!("  AKQJT  ".parse::<Hand<Part1>>().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn lenient_parsing_accepts_one_for_ten() -> anyhow::Result<()> {
+        let hand = Hand::<Part1>::parse("11111 42", true)?;
+        assert_eq!(hand.cards, [Card::N(10); 5]);
+        assert_eq!(hand.outcome(), Outcome::FiveOfAKind);
+        assert_eq!(hand.to_string(), "TTTTT 42");
+
+        assert!("11111 42".parse::<Hand<Part1>>().is_err());
+        assert!(Card::try_from('1').is_err());
+        for (c, n) in ('2'..='9').zip(2..) {
+            assert_eq!(Card::from_char(c, true)?, Card::N(n));
+            assert_eq!(Card::from_char(c, false)?, Card::N(n));
+        }
+
+        Ok(())
     }
 
-    fn part_2(items: Vec<Self::Item>) -> anyhow::Result<Self::Answer> {
-        solve::<Part2>(items)
+    #[test]
+    fn ones_are_accepted_with_the_option() -> anyhow::Result<()> {
+        let lines = Day7::parse_input("32131 765\nKK677 28\n")?;
+        assert!(Day7::build_context(lines.clone()).is_err());
+
+        let options = Options {
+            lenient_cards: true,
+            ..Options::default()
+        };
+        let hands = Day7::build_context(Day7::with_options(lines, &options)?)?;
+        assert_eq!(hands.hands[0].to_string(), "32T3T 765");
+        assert_eq!(Day7::part_1(&hands)?, 765 + 28 * 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn sample_outcome_histogram() -> anyhow::Result<()> {
+        let items = std::fs::read_to_string("src/day7/example.txt")?
+            .lines()
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            outcome_histogram::<Part1>(&items)?,
+            [
+                (Outcome::HighCard, 0),
+                (Outcome::Pair, 1),
+                (Outcome::TwoPair, 2),
+                (Outcome::Set, 2),
+                (Outcome::FullHouse, 0),
+                (Outcome::FourOfAKind, 0),
+                (Outcome::FiveOfAKind, 0),
+            ]
+        );
+
+        let jokers = outcome_histogram::<Part2>(&items)?;
+        assert_eq!(jokers[Outcome::FourOfAKind.value() as usize - 1].1, 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn empty_input_is_rejected() -> anyhow::Result<()> {
+        let options = Options::default();
+        let err = Day7::validate_items(&[], &options).unwrap_err();
+        assert_eq!(err.to_string(), "no hands, is this the right input?");
+        assert!(Day7::validate_items(&["32T3K 765".parse()?], &options).is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn sample_leaderboard() -> anyhow::Result<()> {
+        let items = std::fs::read_to_string("src/day7/example.txt")?
+            .lines()
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+
+        let board = leaderboard::<Part1>(&items)?;
+        assert_eq!(
+            board,
+            "\
+rank | hand  | outcome | bid | score
+   1 | 32T3K | Pair    | 765 |   765
+   2 | KTJJT | TwoPair | 220 |   440
+   3 | KK677 | TwoPair |  28 |    84
+   4 | T55J5 | Set     | 684 |  2736
+   5 | QQQJA | Set     | 483 |  2415
+"
+        );
+
+        // With jokers, the three other hands become four of a kind
+        let board = leaderboard::<Part2>(&items)?;
+        let weakest = board.lines().nth(1).unwrap_or_default();
+        assert_eq!(weakest, "   1 | 32T3K | Pair        | 765 |   765");
+
+        Ok(())
+    }
+
+    #[test]
+    fn jokers_improve_outcomes_not_totals() -> anyhow::Result<()> {
+        let sample = std::fs::read_to_string("src/day7/example.txt")?;
+        let generated = crate::testing::gen_input(7, 500)?;
+
+        for input in [&sample, &generated] {
+            for line in input.lines() {
+                let part_1 = line.parse::<Hand<Part1>>()?.outcome();
+                let part_2 = line.parse::<Hand<Part2>>()?.outcome();
+                assert!(part_2 >= part_1, "{line}: {part_2:?} < {part_1:?}");
+            }
+        }
+
+        // Yet on the sample, part 2 is lower than part 1
+        let hands = Day7::build_context(Day7::parse_input(&sample)?)?;
+        let (part_1, part_2) = (Day7::part_1(&hands)?, Day7::part_2(&hands)?);
+        assert_eq!((part_1, part_2), (6440, 5905));
+        assert!(part_2 < part_1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn equal_hands_rank_in_input_order() -> anyhow::Result<()> {
+        // AoC inputs never hold equal hands, so the ranking of such hands is left to the stable
+        // sort: the first one in the input gets the lowest rank
+        let items = |bids: [u64; 2]| bids.map(|bid| format!("KK677 {bid}")).to_vec();
+
+        assert_eq!(solve_lines::<Part1>(items([10, 3]), false)?, 10 + 3 * 2);
+        assert_eq!(solve_lines::<Part1>(items([3, 10]), false)?, 3 + 10 * 2);
+        assert_eq!(solve_lines::<Part2>(items([10, 3]), false)?, 10 + 3 * 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn strict_rejects_ties() {
+        let items = vec![
+            "32T3K 765".to_string(),
+            "KK677 28".to_string(),
+            "32T3K 100".to_string(),
+        ];
+
+        let err = solve_lines::<Part1>(items.clone(), true).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "hands `32T3K 765` and `32T3K 100` are tied"
+        );
+
+        // 32T3K 765 gets rank 1, 32T3K 100 rank 2 and KK677 rank 3
+        assert_eq!(
+            solve_lines::<Part1>(items, false).unwrap(),
+            765 + 200 + 28 * 3
+        );
+    }
+
+    #[test]
+    fn ties_are_rejected_with_the_option() -> anyhow::Result<()> {
+        let lines = Day7::parse_input("32T3K 765\nKK677 28\n32T3K 100\n")?;
+
+        let hands = Day7::build_context(lines.clone())?;
+        assert_eq!(Day7::part_1(&hands)?, 765 + 200 + 28 * 3);
+
+        let options = Options {
+            strict_ties: true,
+            ..Options::default()
+        };
+        let hands = Day7::build_context(Day7::with_options(lines, &options)?)?;
+        assert!(Day7::part_1(&hands).is_err());
+        assert!(Day7::part_2(&hands).is_err());
+
+        Ok(())
+    }
+
+    /// The best outcome of a hand under part 2 rules, trying every card for every joker
+    fn brute_force_joker_outcome(cards: &[Card; 5]) -> Outcome {
+        let jokers = cards
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, c)| (*c == Card::Jack).then_some(idx))
+            .collect::<Vec<_>>();
+
+        substitutions(*cards, jokers)
+            .map(|cards| Part1::outcome(&cards))
+            .max()
+            .unwrap()
+    }
+
+    #[test]
+    fn jokers_match_brute_force() {
+        for indexes in multisets() {
+            let cards = indexes.map(|i| Card::ALL[i]);
+            assert_eq!(
+                Part2::outcome(&cards),
+                brute_force_joker_outcome(&cards),
+                "{cards:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn every_multiset_has_exactly_one_outcome() {
+        let expectations: &[(Outcome, &[usize])] = &[
+            (Outcome::FiveOfAKind, &[5]),
+            (Outcome::FourOfAKind, &[4, 1]),
+            (Outcome::FullHouse, &[3, 2]),
+            (Outcome::Set, &[3, 1, 1]),
+            (Outcome::TwoPair, &[2, 2, 1]),
+            (Outcome::Pair, &[2, 1, 1, 1]),
+            (Outcome::HighCard, &[1, 1, 1, 1, 1]),
+        ];
+
+        let mut total = 0;
+        for indexes in multisets() {
+            let cards = indexes.map(|i| Card::ALL[i]);
+            let shape = shape(&cards);
+
+            let matching = expectations
+                .iter()
+                .filter(|(_, s)| *s == shape.as_slice())
+                .map(|(o, _)| *o)
+                .collect::<Vec<_>>();
+
+            assert_eq!(matching.len(), 1, "{cards:?} matches {matching:?}");
+            assert_eq!(Part1::outcome(&cards), matching[0], "{cards:?}");
+            total += 1;
+        }
+
+        // C(13 + 5 - 1, 5)
+        assert_eq!(total, 6188);
     }
 }
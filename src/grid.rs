@@ -0,0 +1,200 @@
+//! A two dimensional grid of cells, read from the lines of an input
+
+/// The offsets of the orthogonal neighbors of a cell, in rows and columns
+const ORTHOGONAL: &[(isize, isize)] = &[
+    (0, -1), // LEFT
+    (0, 1),  // RIGHT
+    (-1, 0), // UP
+    (1, 0),  // DOWN
+];
+
+/// The offsets of the diagonal neighbors of a cell, in rows and columns
+const DIAGONAL: &[(isize, isize)] = &[
+    (-1, -1), // LEFT UP
+    (-1, 1),  // RIGHT UP
+    (1, -1),  // LEFT DOWN
+    (1, 1),   // RIGHT DOWN
+];
+
+/// The positions at the given offsets of `(row, column)`, those that don't underflow
+fn offset_positions(
+    row: usize,
+    column: usize,
+    offsets: impl Iterator<Item = &'static (isize, isize)>,
+) -> impl Iterator<Item = (usize, usize)> {
+    offsets.filter_map(move |(offset_row, offset_column)| {
+        Some((
+            row.checked_add_signed(*offset_row)?,
+            column.checked_add_signed(*offset_column)?,
+        ))
+    })
+}
+
+/// The cells of a grid, row by row. Rows may be of differing widths, as long as the lines of the
+/// input are
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(super) struct Grid<T> {
+    cells: Box<[T]>,
+    /// Index in `cells` of the start of every row, followed by the length of `cells`
+    offsets: Vec<usize>,
+}
+
+impl<T> Grid<T> {
+    /// A grid with a row for every line, and a cell for every character of the lines
+    pub(super) fn from_lines<S: AsRef<str>>(lines: impl IntoIterator<Item = S>) -> Self
+    where
+        T: From<char>,
+    {
+        let mut cells = Vec::new();
+        let mut offsets = vec![0];
+        for line in lines {
+            cells.extend(line.as_ref().chars().map(T::from));
+            offsets.push(cells.len());
+        }
+
+        Self {
+            cells: cells.into_boxed_slice(),
+            offsets,
+        }
+    }
+
+    pub(super) fn rows(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    /// The width of the widest row
+    #[cfg(test)]
+    pub(super) fn cols(&self) -> usize {
+        self.offsets
+            .windows(2)
+            .map(|w| w[1] - w[0])
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub(super) fn row(&self, row: usize) -> Option<&[T]> {
+        let start = *self.offsets.get(row)?;
+        let end = *self.offsets.get(row + 1)?;
+        self.cells.get(start..end)
+    }
+
+    pub(super) fn get(&self, row: usize, column: usize) -> Option<&T> {
+        self.row(row)?.get(column)
+    }
+
+    /// The position of the cell at `idx` when reading the grid row by row
+    pub(super) fn position(&self, idx: usize) -> (usize, usize) {
+        let row = self.offsets.partition_point(|offset| *offset <= idx) - 1;
+        (row, idx - self.offsets[row])
+    }
+
+    /// Every cell along with its position, row by row
+    pub(super) fn iter(&self) -> impl Iterator<Item = (usize, usize, &T)> {
+        self.cells.iter().enumerate().map(|(idx, cell)| {
+            let (row, column) = self.position(idx);
+            (row, column, cell)
+        })
+    }
+
+    /// The cells orthogonally and diagonally around `(row, column)` that are inside of the grid,
+    /// along with their position
+    pub(super) fn neighbors8(
+        &self,
+        row: usize,
+        column: usize,
+    ) -> impl Iterator<Item = (usize, usize, &T)> {
+        self.neighbors4(row, column)
+            .chain(self.cells_at(offset_positions(row, column, DIAGONAL.iter())))
+    }
+
+    /// The cells orthogonally around `(row, column)` that are inside of the grid, along with their
+    /// position
+    pub(super) fn neighbors4(
+        &self,
+        row: usize,
+        column: usize,
+    ) -> impl Iterator<Item = (usize, usize, &T)> {
+        self.cells_at(offset_positions(row, column, ORTHOGONAL.iter()))
+    }
+
+    fn cells_at(
+        &self,
+        positions: impl Iterator<Item = (usize, usize)>,
+    ) -> impl Iterator<Item = (usize, usize, &T)> {
+        positions.filter_map(|(row, column)| Some((row, column, self.get(row, column)?)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn grid(lines: &[&str]) -> Grid<char> {
+        Grid::from_lines(lines)
+    }
+
+    fn positions<'a, T: 'a>(
+        neighbors: impl Iterator<Item = (usize, usize, &'a T)>,
+    ) -> Vec<(usize, usize)> {
+        neighbors.map(|(row, column, _)| (row, column)).collect()
+    }
+
+    #[test]
+    fn cells_of_ragged_rows() {
+        let grid = grid(&["467..", "...*......", "", "35"]);
+        assert_eq!(grid.rows(), 4);
+        assert_eq!(grid.cols(), 10);
+
+        assert_eq!(grid.get(0, 0), Some(&'4'));
+        assert_eq!(grid.get(0, 4), Some(&'.'));
+        assert_eq!(grid.get(0, 5), None);
+        assert_eq!(grid.get(1, 3), Some(&'*'));
+        assert_eq!(grid.get(1, 9), Some(&'.'));
+        assert_eq!(grid.get(1, 10), None);
+        assert_eq!(grid.get(2, 0), None);
+        assert_eq!(grid.get(3, 1), Some(&'5'));
+        assert_eq!(grid.get(4, 0), None);
+        assert_eq!(grid.row(2), Some(&[][..]));
+        assert_eq!(grid.row(4), None);
+
+        assert_eq!(grid.position(5), (1, 0));
+        assert_eq!(grid.position(15), (3, 0));
+        assert_eq!(
+            grid.iter().filter(|(_, _, c)| c.is_ascii_digit()).count(),
+            5
+        );
+        assert_eq!(grid.iter().last(), Some((3, 1, &'5')));
+    }
+
+    #[test]
+    fn empty_grid() {
+        let grid = Grid::<char>::from_lines(Vec::<String>::new());
+        assert_eq!((grid.rows(), grid.cols()), (0, 0));
+        assert_eq!(grid.get(0, 0), None);
+        assert_eq!(grid.neighbors8(0, 0).count(), 0);
+    }
+
+    #[test]
+    fn neighbors_stay_inside_of_the_grid() {
+        let grid = grid(&["1*.", "23.", "..#"]);
+
+        assert_eq!(
+            positions(grid.neighbors8(0, 1)),
+            [(0, 0), (0, 2), (1, 1), (1, 0), (1, 2)]
+        );
+        assert_eq!(positions(grid.neighbors4(0, 1)), [(0, 0), (0, 2), (1, 1)]);
+        assert_eq!(grid.neighbors8(1, 1).count(), 8);
+        assert_eq!(grid.neighbors4(1, 1).count(), 4);
+        assert_eq!(
+            grid.neighbors8(2, 2)
+                .map(|(_, _, c)| *c)
+                .collect::<String>(),
+            "..3"
+        );
+
+        // Past the end of a shorter row
+        let grid = self::grid(&["...45", "*"]);
+        assert_eq!(positions(grid.neighbors8(1, 0)), [(0, 0), (0, 1)]);
+        assert_eq!(positions(grid.neighbors8(0, 4)), [(0, 3)]);
+    }
+}
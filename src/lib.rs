@@ -0,0 +1,282 @@
+//! Solutions of the Advent of Code 2023, along with the command line that runs them
+
+mod cache;
+mod cli;
+mod day;
+mod day1;
+mod day2;
+mod day3;
+mod day4;
+mod day5;
+mod day6;
+mod day7;
+mod grid;
+#[cfg(test)]
+mod testing;
+mod util;
+#[cfg(test)]
+mod verify;
+use std::{
+    ffi::OsString,
+    io::{self, Write},
+    net::TcpStream,
+    path::{Path, PathBuf},
+};
+
+use anyhow::anyhow;
+use cli::{Args, Output, RunSpec};
+
+use day::{DayReport, Options, Part, Total, UnsolvedParts};
+use day1::Day1;
+use day2::Day2;
+use day3::Day3;
+use day4::Day4;
+use day5::Day5;
+use day6::Day6;
+use day7::Day7;
+
+pub use cache::Answers;
+#[doc(hidden)]
+pub use day5::Planting;
+
+type Runner = fn(&Path, Part, &Options) -> DayReport;
+type Solver = fn(&str, Part) -> anyhow::Result<Answers>;
+
+/// Every solved day, along with the parts to run by default
+const DAYS: &[(usize, Part, Runner, Solver)] = &[
+    (1, Part::All, day::report::<Day1>, day::solve_str::<Day1>),
+    (2, Part::All, day::report::<Day2>, day::solve_str::<Day2>),
+    (3, Part::All, day::report::<Day3>, day::solve_str::<Day3>),
+    (4, Part::All, day::report::<Day4>, day::solve_str::<Day4>),
+    (5, Part::All, day::report::<Day5>, day::solve_str::<Day5>),
+    (6, Part::All, day::report::<Day6>, day::solve_str::<Day6>),
+    (7, Part::All, day::report::<Day7>, day::solve_str::<Day7>),
+];
+
+/// The directory holding the `dayN/input.txt` inputs, unless `AOC_INPUT_DIR` is set
+const DEFAULT_INPUT_DIR: &str = "src";
+
+/// The path of the input of `day`, in the directory given by `AOC_INPUT_DIR`
+pub fn input_path(day: usize) -> PathBuf {
+    input_path_from(std::env::var_os("AOC_INPUT_DIR"), day)
+}
+
+fn input_path_from(dir: Option<OsString>, day: usize) -> PathBuf {
+    let dir = dir
+        .filter(|dir| !dir.is_empty())
+        .map_or_else(|| PathBuf::from(DEFAULT_INPUT_DIR), PathBuf::from);
+    dir.join(format!("day{day}")).join("input.txt")
+}
+
+/// Solves every day, reading the input of each one from the path given by `input`
+fn run_all(input: impl Fn(usize) -> PathBuf, options: &Options) -> Vec<DayReport> {
+    DAYS.iter()
+        .map(|(day, part, run, _)| run(&input(*day), *part, options))
+        .collect()
+}
+
+/// Solves the days of `spec`, reading the input of each one from the path given by `input`
+fn run_spec(
+    spec: &RunSpec,
+    input: impl Fn(usize) -> PathBuf,
+    options: &Options,
+) -> anyhow::Result<Vec<DayReport>> {
+    spec.0
+        .iter()
+        .map(|(day, part)| {
+            let (_, default_part, run, _) = DAYS
+                .iter()
+                .find(|(d, _, _, _)| d == day)
+                .ok_or(anyhow!("day {day} is not solved yet"))?;
+            Ok(run(&input(*day), part.unwrap_or(*default_part), options))
+        })
+        .collect()
+}
+
+/// Solves the default parts of `day` from the content of its input, which is the entry point of
+/// callers that have no file system, like a browser
+pub fn solve_str(day: usize, input: &str) -> anyhow::Result<Answers> {
+    let (_, part, _, solve) = DAYS
+        .iter()
+        .find(|(d, _, _, _)| *d == day)
+        .ok_or(anyhow!("day {day} is not solved yet"))?;
+    solve(input, *part)
+}
+
+/// Writes every report as a line of JSON
+fn write_json_lines(mut out: impl Write, reports: &[DayReport]) -> io::Result<()> {
+    for report in reports {
+        writeln!(out, "{}", report.to_json())?;
+    }
+    out.flush()
+}
+
+/// Sends the reports as lines of JSON to the TCP socket at `addr`
+fn emit(addr: &str, reports: &[DayReport]) -> io::Result<()> {
+    write_json_lines(TcpStream::connect(addr)?, reports)
+}
+
+/// Runs the command line with the arguments of the process
+pub fn run() -> Result<(), Box<dyn std::error::Error>> {
+    let Args {
+        options,
+        run,
+        input,
+        output,
+    } = cli::parse_args(std::env::args().skip(1))?;
+
+    let input = |day| input.clone().unwrap_or_else(|| input_path(day));
+    let reports = match run {
+        Some(spec) => run_spec(&spec, input, &options)?,
+        None => run_all(input, &options),
+    };
+
+    match output {
+        Output::Text => {
+            for report in &reports {
+                print!("{report:.precision$}", precision = options.precision);
+            }
+            print!("{}", Total::of(&reports));
+            print!("{}", UnsolvedParts::of(&reports));
+        }
+        Output::Json => write_json_lines(io::stdout().lock(), &reports)?,
+        Output::Tcp(addr) => {
+            if let Err(e) = emit(&addr, &reports) {
+                eprintln!("failed to emit the answers to {addr}: {e}, printing them instead");
+                write_json_lines(io::stdout().lock(), &reports)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn run_all_reports_every_day() {
+        let reports = run_all(
+            |day| match day {
+                1 => PathBuf::from("src/day1/example_part1.txt"),
+                day => PathBuf::from(format!("src/day{day}/example.txt")),
+            },
+            &Options::default(),
+        );
+        assert_eq!(reports.len(), DAYS.len());
+
+        let day1 = reports
+            .iter()
+            .find(|r| r.day == 1)
+            .expect("day 1 is reported");
+        assert_eq!(day1.part1.as_deref(), Some("142"));
+        assert!(day1.errors.is_empty());
+
+        let day5 = reports
+            .iter()
+            .find(|r| r.day == 5)
+            .expect("day 5 is reported");
+        assert_eq!(day5.part1.as_deref(), Some("35"));
+        assert_eq!(day5.part2.as_deref(), Some("46"));
+    }
+
+    #[test]
+    fn solve_str_without_files() -> anyhow::Result<()> {
+        let games = "\
+Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+        let answers = solve_str(2, games)?;
+        assert_eq!(answers.part1.as_deref(), Some("8"));
+        assert_eq!(answers.part2.as_deref(), Some("2286"));
+
+        let answers = solve_str(7, "\u{feff}32T3K 765\nT55J5 684\nKK677 28")?;
+        assert_eq!(answers.part1.as_deref(), Some("2873"));
+
+        assert!(solve_str(2, "Game 1: 3 yellow").is_err());
+        assert_eq!(
+            solve_str(12, "").unwrap_err().to_string(),
+            "day 12 is not solved yet"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn emit_json_lines_over_tcp() -> anyhow::Result<()> {
+        use std::io::BufRead;
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?.to_string();
+        let received = std::thread::spawn(move || {
+            let (stream, _) = listener.accept()?;
+            io::BufReader::new(stream)
+                .lines()
+                .collect::<io::Result<Vec<_>>>()
+        });
+
+        let spec = "2,6:1".parse::<RunSpec>()?;
+        let input = |day| PathBuf::from(format!("src/day{day}/example.txt"));
+        let reports = run_spec(&spec, input, &Options::default())?;
+        emit(&addr, &reports)?;
+
+        let received = received.join().expect("listener panicked")?;
+        assert_eq!(
+            received,
+            [
+                r#"{"day":2,"part1":"8","part2":"2286","errors":[],"cached":false}"#,
+                r#"{"day":6,"part1":"288","part2":null,"errors":[],"cached":false}"#,
+            ]
+        );
+
+        // Nothing listens on the port anymore
+        assert!(emit(&addr, &reports).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn input_dir_from_env() {
+        assert_eq!(
+            input_path_from(None, 5),
+            PathBuf::from("src/day5/input.txt")
+        );
+        assert_eq!(
+            input_path_from(Some("".into()), 5),
+            PathBuf::from("src/day5/input.txt")
+        );
+        assert_eq!(
+            input_path_from(Some("/tmp/aoc".into()), 12),
+            PathBuf::from("/tmp/aoc/day12/input.txt")
+        );
+    }
+
+    #[test]
+    fn run_spec_dispatches_parts() -> anyhow::Result<()> {
+        let input = |day| PathBuf::from(format!("src/day{day}/example.txt"));
+        let spec = "2:2,6,7:1".parse::<RunSpec>()?;
+
+        let reports = run_spec(&spec, input, &Options::default())?;
+        let runs = reports
+            .iter()
+            .map(|r| (r.day, r.part, r.part1.as_deref(), r.part2.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            runs,
+            [
+                (2, Part::Two, None, Some("2286")),
+                (6, Part::All, Some("288"), Some("71503")),
+                (7, Part::One, Some("6440"), None),
+            ]
+        );
+
+        let unsolved = "7,12".parse::<RunSpec>()?;
+        let err = run_spec(&unsolved, input, &Options::default()).unwrap_err();
+        assert_eq!(err.to_string(), "day 12 is not solved yet");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,224 @@
+//! Helpers shared by the tests of the different days
+
+use std::{fmt::Display, str::FromStr};
+
+use crate::day::Day;
+
+/// A small xorshift generator, deterministic so that generated inputs are reproducible
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        // xorshift gets stuck on a zero state
+        Self(seed.max(1))
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+
+    /// Returns a number in `[low, high)`
+    pub(crate) fn range(&mut self, low: u64, high: u64) -> u64 {
+        low + self.next_u64() % (high - low)
+    }
+
+    pub(crate) fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.range(0, items.len() as u64) as usize]
+    }
+
+    pub(crate) fn chance(&mut self, percent: u64) -> bool {
+        self.range(0, 100) < percent
+    }
+}
+
+fn gen_day1(rng: &mut Rng, size: usize) -> String {
+    const WORDS: &[&str] = &[
+        "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+    ];
+
+    let mut lines = Vec::with_capacity(size);
+    for _ in 0..size {
+        let mut line = String::new();
+        for _ in 0..rng.range(1, 6) {
+            match rng.range(0, 3) {
+                0 => line.push_str(rng.pick::<&str>(WORDS)),
+                1 => line.push((b'a' + rng.range(0, 26) as u8) as char),
+                _ => line.push((b'0' + rng.range(1, 10) as u8) as char),
+            }
+        }
+
+        // Part 1 needs at least one digit on every line
+        line.push((b'0' + rng.range(1, 10) as u8) as char);
+        lines.push(line);
+    }
+
+    lines.join("\n")
+}
+
+fn gen_day2(rng: &mut Rng, size: usize) -> String {
+    const COLORS: &[&str] = &["red", "green", "blue"];
+
+    (1..=size)
+        .map(|id| {
+            let rounds = (0..rng.range(1, 6))
+                .map(|_| {
+                    (0..rng.range(1, 4))
+                        .map(|_| format!("{} {}", rng.range(1, 21), rng.pick(COLORS)))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+                .collect::<Vec<_>>()
+                .join("; ");
+            format!("Game {id}: {rounds}")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn gen_day3(rng: &mut Rng, size: usize) -> String {
+    const SYMBOLS: &[char] = &['*', '#', '+', '$', '/', '=', '%', '@', '&', '-'];
+
+    let mut lines = Vec::with_capacity(size);
+    for _ in 0..size {
+        let mut line = String::with_capacity(size);
+        while line.len() < size {
+            let remaining = size - line.len();
+            if rng.chance(15) {
+                let digits = rng.range(1, 4).min(remaining as u64);
+                for _ in 0..digits {
+                    line.push((b'0' + rng.range(0, 10) as u8) as char);
+                }
+                if line.len() < size {
+                    line.push('.');
+                }
+            } else if rng.chance(10) {
+                line.push(*rng.pick(SYMBOLS));
+            } else {
+                line.push('.');
+            }
+        }
+        lines.push(line);
+    }
+
+    lines.join("\n")
+}
+
+fn gen_day4(rng: &mut Rng, size: usize) -> String {
+    let mut numbers = || {
+        (0..rng.range(3, 10))
+            .map(|_| format!("{:>2}", rng.range(1, 100)))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+
+    (1..=size)
+        .map(|id| format!("Card {id:>3}: {} | {}", numbers(), numbers()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn gen_day7(rng: &mut Rng, size: usize) -> String {
+    const CARDS: &[char] = &[
+        '2', '3', '4', '5', '6', '7', '8', '9', 'T', 'J', 'Q', 'K', 'A',
+    ];
+
+    (0..size)
+        .map(|_| {
+            let hand = (0..5).map(|_| *rng.pick(CARDS)).collect::<String>();
+            format!("{hand} {}", rng.range(1, 1000))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Generates a syntactically valid input of roughly `size` lines for the given day, failing for the
+/// days without a generator
+pub(crate) fn gen_input(day: usize, size: usize) -> anyhow::Result<String> {
+    let mut rng = Rng::new(day as u64 * 7919 + size as u64);
+
+    Ok(match day {
+        1 => gen_day1(&mut rng, size),
+        2 => gen_day2(&mut rng, size),
+        3 => gen_day3(&mut rng, size),
+        4 => gen_day4(&mut rng, size),
+        7 => gen_day7(&mut rng, size),
+        _ => anyhow::bail!("no input generator for day {day}"),
+    })
+}
+
+/// Parses every line of `input` as an item of the given day
+pub(crate) fn parse_items<D: Day>(input: &str) -> anyhow::Result<Vec<D::Item>>
+where
+    <D::Item as FromStr>::Err: Display,
+{
+    input
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| {
+            line.parse()
+                .map_err(|e| anyhow::anyhow!("line {}: {e}", idx + 1))
+        })
+        .collect()
+}
+
+/// Asserts that every line of `input` is printed back as it is written once parsed as an item of
+/// the given day, leading and trailing whitespace aside
+pub(crate) fn assert_round_trip<D: Day>(input: &str) -> anyhow::Result<()>
+where
+    D::Item: Display,
+    <D::Item as FromStr>::Err: Display,
+{
+    let items = parse_items::<D>(input)?;
+    for (item, line) in items.iter().zip(input.lines()) {
+        assert_eq!(item.to_string().trim(), line.trim());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{day1::Day1, day2::Day2, day3::Day3, day4::Day4, day7::Day7};
+
+    fn check<D: Day>(size: usize) -> anyhow::Result<()>
+    where
+        <D::Item as FromStr>::Err: Display,
+    {
+        let input = gen_input(D::DAY, size)?;
+        let items = parse_items::<D>(&input)?;
+        assert_eq!(items.len(), size);
+        D::part_1(&D::build_context(items)?)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn generated_inputs_parse() -> anyhow::Result<()> {
+        check::<Day1>(200)?;
+        check::<Day2>(200)?;
+        check::<Day3>(200)?;
+        check::<Day4>(200)?;
+        check::<Day7>(200)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn generated_inputs_are_deterministic() -> anyhow::Result<()> {
+        assert_eq!(gen_input(7, 50)?, gen_input(7, 50)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn days_without_a_generator_are_an_error() {
+        assert!(gen_input(5, 10).is_err());
+        assert!(gen_input(6, 10).is_err());
+    }
+}
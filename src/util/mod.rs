@@ -0,0 +1,357 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    hash::Hash,
+    str::FromStr,
+    time::Duration,
+};
+
+use anyhow::{anyhow, bail};
+
+#[cfg(test)]
+pub(super) mod term;
+
+/// Removes duplicated items, as identified by `key`, keeping the first occurrence of each one
+pub(super) fn dedup_by_key<T, K: Hash + Eq>(
+    items: impl IntoIterator<Item = T>,
+    key: impl Fn(&T) -> K,
+) -> Vec<T> {
+    let mut seen = HashSet::new();
+    items
+        .into_iter()
+        .filter(|item| seen.insert(key(item)))
+        .collect()
+}
+
+/// Wraps the pure function `f` so that it is computed once for every distinct input
+pub(super) fn memoize<K: Hash + Eq + Clone, V: Clone>(f: impl Fn(&K) -> V) -> impl FnMut(&K) -> V {
+    let mut cache = HashMap::new();
+    move |key| {
+        if let Some(value) = cache.get(key) {
+            return V::clone(value);
+        }
+
+        let value = f(key);
+        cache.insert(key.clone(), value.clone());
+        value
+    }
+}
+
+/// Returns the trimmed values of a `header: values` line, after checking that its header starts
+/// with `prefix`
+pub(super) fn after_colon<'a>(s: &'a str, prefix: &str) -> anyhow::Result<&'a str> {
+    let (header, values) = s.split_once(':').ok_or(anyhow!("missing `:` in `{s}`"))?;
+
+    if !header.starts_with(prefix) {
+        bail!("expected `{prefix}`, got `{header}`");
+    }
+
+    Ok(values.trim())
+}
+
+/// Parses a `label: n n n` line into its trimmed label and its numbers
+pub(super) fn parse_labeled_numbers<T: FromStr>(s: &str) -> anyhow::Result<(String, Vec<T>)>
+where
+    T::Err: Display,
+{
+    let (label, numbers) = s.split_once(':').ok_or(anyhow!("missing `:` in `{s}`"))?;
+    let numbers = numbers
+        .split_whitespace()
+        .map(|n| n.parse().map_err(|e| anyhow!("invalid number `{n}`: {e}")))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    Ok((label.trim().to_string(), numbers))
+}
+
+/// Cuts `s` at the first `#` that is not escaped by a `\`, dropping the comment it starts
+pub(super) fn strip_comment(s: &str) -> &str {
+    let mut escaped = false;
+    for (idx, c) in s.char_indices() {
+        match c {
+            '#' if !escaped => return &s[..idx],
+            '\\' => escaped = !escaped,
+            _ => escaped = false,
+        }
+    }
+    s
+}
+
+/// Splits `lines` into the header lines found before the first blank line, and the blocks of
+/// lines separated by blank lines after it
+pub(super) fn split_sections<S: AsRef<str>>(lines: &[S]) -> (Vec<&S>, Vec<&[S]>) {
+    let is_blank = |line: &S| line.as_ref().trim().is_empty();
+
+    let header_len = lines.iter().position(is_blank).unwrap_or(lines.len());
+    let (header, rest) = lines.split_at(header_len);
+
+    let blocks = rest
+        .split(is_blank)
+        .filter(|block| !block.is_empty())
+        .collect();
+    (header.iter().collect(), blocks)
+}
+
+/// How many integers lie in the open interval `(lo, hi)`, the bounds being excluded even when they
+/// are integers
+pub(super) fn count_integers_strictly_between(lo: f64, hi: f64) -> u64 {
+    let first = lo.floor() + 1.0;
+    let last = hi.ceil() - 1.0;
+    if last < first {
+        return 0;
+    }
+
+    (last - first) as u64 + 1
+}
+
+/// How many integers lie in the closed interval `[lo, hi]`
+pub(super) fn count_integers_between(lo: f64, hi: f64) -> u64 {
+    let first = lo.ceil();
+    let last = hi.floor();
+    if last < first {
+        return 0;
+    }
+
+    (last - first) as u64 + 1
+}
+
+/// A row of whitespace separated numbers, the item of the days whose input is a numeric matrix
+#[allow(dead_code)]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(super) struct NumRow(pub(super) Vec<i64>);
+
+impl FromStr for NumRow {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let numbers = s
+            .split_whitespace()
+            .map(|n| n.parse().map_err(|e| anyhow!("invalid number `{n}`: {e}")))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        Ok(Self(numbers))
+    }
+}
+
+/// Maps every window of `size` consecutive items of `slice`, there are none when `slice` is shorter
+/// than `size`
+pub(super) fn windows_map<T, R>(slice: &[T], size: usize, f: impl Fn(&[T]) -> R) -> Vec<R> {
+    slice.windows(size).map(f).collect()
+}
+
+/// Repeatedly computes the differences between consecutive values, starting with `seq` itself and
+/// stopping at the first row made of zeroes only
+#[allow(dead_code)]
+pub(super) fn difference_table(seq: &[i64]) -> Vec<Vec<i64>> {
+    let mut rows = vec![seq.to_vec()];
+
+    while let Some(last) = rows.last().filter(|row| row.iter().any(|n| *n != 0)) {
+        let next = windows_map(last, 2, |w| w[1] - w[0]);
+        rows.push(next);
+    }
+
+    rows
+}
+
+/// Formats `d` in the largest unit in which it is at least 1, rounded to `sig` significant digits
+pub(super) fn format_duration(d: Duration, sig: usize) -> String {
+    const UNITS: &[(&str, f64)] = &[("s", 1e9), ("ms", 1e6), ("µs", 1e3), ("ns", 1.0)];
+
+    let nanos = d.as_nanos() as f64;
+    let (unit, scale) = UNITS
+        .iter()
+        .find(|(_, scale)| nanos >= *scale)
+        .unwrap_or(&UNITS[UNITS.len() - 1]);
+
+    let value = nanos / scale;
+    let integer_digits = (value.log10().floor() as usize).saturating_add(1);
+    // Nanoseconds being the finest resolution, they don't have decimals
+    let decimals = match *unit {
+        "ns" => 0,
+        _ => sig.saturating_sub(integer_digits),
+    };
+    format!("{value:.decimals$}{unit}")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn dedup_keeps_first_occurrence() {
+        let items = vec![(1, 'a'), (2, 'b'), (1, 'c'), (3, 'd'), (2, 'e')];
+        assert_eq!(
+            dedup_by_key(items, |(k, _)| *k),
+            vec![(1, 'a'), (2, 'b'), (3, 'd')]
+        );
+    }
+
+    #[test]
+    fn memoize_computes_once() {
+        let calls = std::cell::Cell::new(0);
+        let mut square = memoize(|n: &u64| {
+            calls.set(calls.get() + 1);
+            n * n
+        });
+
+        let squares = [3, 4, 3, 3, 5, 4].map(|n| square(&n));
+        assert_eq!(squares, [9, 16, 9, 9, 25, 16]);
+        assert_eq!(calls.get(), 3);
+    }
+
+    #[test]
+    fn after_colon_with_prefix() -> anyhow::Result<()> {
+        assert_eq!(
+            after_colon("Card 1: 41 48 | 83 86", "Card")?,
+            "41 48 | 83 86"
+        );
+        assert_eq!(after_colon("seeds: 79 14 55 13", "seeds")?, "79 14 55 13");
+        assert_eq!(
+            after_colon("Time:      7  15   30  ", "Time")?,
+            "7  15   30"
+        );
+        assert_eq!(after_colon("Time:", "Time")?, "");
+
+        Ok(())
+    }
+
+    #[test]
+    fn labeled_numbers() -> anyhow::Result<()> {
+        assert_eq!(
+            parse_labeled_numbers::<u64>("seeds: 79 14 55 13")?,
+            ("seeds".to_string(), vec![79, 14, 55, 13])
+        );
+        assert_eq!(
+            parse_labeled_numbers::<u32>("Time:      7  15   30  ")?,
+            ("Time".to_string(), vec![7, 15, 30])
+        );
+        assert_eq!(
+            parse_labeled_numbers::<u32>("Distance:")?,
+            ("Distance".to_string(), Vec::new())
+        );
+
+        assert!(parse_labeled_numbers::<u32>("seeds 79 14").is_err());
+        let err = parse_labeled_numbers::<u32>("seeds: 79 x").unwrap_err();
+        assert!(err.to_string().starts_with("invalid number `x`"), "{err}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn comments_are_stripped() {
+        let numbers = strip_comment("1 2 3 # three numbers")
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<Vec<u32>, _>>();
+        assert_eq!(numbers, Ok(vec![1, 2, 3]));
+
+        assert_eq!(strip_comment("1 2 3"), "1 2 3");
+        assert_eq!(strip_comment("# only a comment"), "");
+        assert_eq!(strip_comment("a \\# b # c"), "a \\# b ");
+        assert_eq!(strip_comment("a \\\\# b"), "a \\\\");
+    }
+
+    #[test]
+    fn after_colon_with_wrong_prefix() {
+        let err = after_colon("Distance: 9 40 200", "Time").unwrap_err();
+        assert_eq!(err.to_string(), "expected `Time`, got `Distance`");
+    }
+
+    #[test]
+    fn after_colon_without_colon() {
+        let err = after_colon("Time 7 15 30", "Time").unwrap_err();
+        assert_eq!(err.to_string(), "missing `:` in `Time 7 15 30`");
+    }
+
+    #[test]
+    fn split_sections_of_almanac() {
+        let lines = [
+            "seeds: 79 14 55 13",
+            "",
+            "seed-to-soil map:",
+            "50 98 2",
+            "52 50 48",
+            "",
+            "",
+            "soil-to-fertilizer map:",
+            "0 15 37",
+            "",
+        ]
+        .map(String::from);
+
+        let (header, blocks) = split_sections(&lines);
+        assert_eq!(header, vec!["seeds: 79 14 55 13"]);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0], ["seed-to-soil map:", "50 98 2", "52 50 48"]);
+        assert_eq!(blocks[1], ["soil-to-fertilizer map:", "0 15 37"]);
+
+        let (header, blocks) = split_sections(&lines[..1]);
+        assert_eq!(header.len(), 1);
+        assert!(blocks.is_empty());
+    }
+
+    #[test]
+    fn format_duration_significant_digits() {
+        let format = |nanos, sig| format_duration(Duration::from_nanos(nanos), sig);
+
+        assert_eq!(format(1_234_567, 3), "1.23ms");
+        assert_eq!(format(500, 3), "500ns");
+        assert_eq!(format(1_234_567, 5), "1.2346ms");
+        assert_eq!(format(1_234_567, 1), "1ms");
+        assert_eq!(format(12_345, 3), "12.3µs");
+        assert_eq!(format(2_500_000_000, 3), "2.50s");
+        assert_eq!(format(0, 3), "0ns");
+    }
+
+    #[test]
+    fn integers_strictly_between() {
+        assert_eq!(count_integers_strictly_between(2.0, 5.0), 2);
+        assert_eq!(count_integers_strictly_between(2.5, 5.5), 3);
+        assert_eq!(count_integers_strictly_between(2.0, 5.5), 3);
+        assert_eq!(count_integers_strictly_between(2.5, 5.0), 2);
+        assert_eq!(count_integers_strictly_between(2.0, 3.0), 0);
+        assert_eq!(count_integers_strictly_between(2.2, 2.8), 0);
+        assert_eq!(count_integers_strictly_between(5.0, 2.0), 0);
+        assert_eq!(count_integers_strictly_between(-1.5, 1.5), 3);
+
+        assert_eq!(count_integers_between(2.0, 5.0), 4);
+        assert_eq!(count_integers_between(2.5, 5.5), 3);
+        assert_eq!(count_integers_between(2.0, 2.0), 1);
+        assert_eq!(count_integers_between(2.2, 2.8), 0);
+        assert_eq!(count_integers_between(5.0, 2.0), 0);
+    }
+
+    #[test]
+    fn num_row_with_negatives() -> anyhow::Result<()> {
+        assert_eq!("-1 2 -3".parse::<NumRow>()?, NumRow(vec![-1, 2, -3]));
+        assert_eq!("  10   -20 ".parse::<NumRow>()?, NumRow(vec![10, -20]));
+        assert_eq!("".parse::<NumRow>()?, NumRow(Vec::new()));
+        assert!("1 - 2".parse::<NumRow>().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn windows_map_differences() {
+        assert_eq!(windows_map(&[1, 3, 6], 2, |w| w[1] - w[0]), [2, 3]);
+        assert_eq!(windows_map(&[1, 3, 6], 3, |w| w.iter().sum::<i32>()), [10]);
+        assert!(windows_map(&[1], 2, |w| w[1] - w[0]).is_empty());
+    }
+
+    #[test]
+    fn difference_table_down_to_zeroes() {
+        assert_eq!(
+            difference_table(&[0, 3, 6, 9]),
+            vec![vec![0, 3, 6, 9], vec![3, 3, 3], vec![0, 0]]
+        );
+        assert_eq!(
+            difference_table(&[1, 3, 6, 10, 15, 21]),
+            vec![
+                vec![1, 3, 6, 10, 15, 21],
+                vec![2, 3, 4, 5, 6],
+                vec![1, 1, 1, 1],
+                vec![0, 0, 0],
+            ]
+        );
+        assert_eq!(difference_table(&[0, 0]), vec![vec![0, 0]]);
+    }
+}
@@ -0,0 +1,39 @@
+use std::{
+    ffi::OsStr,
+    io::{self, IsTerminal},
+};
+
+/// Whether colored output should be emitted on stdout: never when the `NO_COLOR` environment
+/// variable is set to a non-empty value, and only when stdout is a terminal otherwise
+pub(crate) fn color_enabled() -> bool {
+    color_policy(
+        std::env::var_os("NO_COLOR").as_deref(),
+        io::stdout().is_terminal(),
+    )
+}
+
+fn color_policy(no_color: Option<&OsStr>, is_terminal: bool) -> bool {
+    match no_color {
+        Some(value) if !value.is_empty() => false,
+        _ => is_terminal,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn no_color_forces_color_off() {
+        assert!(!color_policy(Some(OsStr::new("1")), true));
+        assert!(!color_policy(Some(OsStr::new("1")), false));
+    }
+
+    #[test]
+    fn color_follows_terminal() {
+        assert!(color_policy(None, true));
+        assert!(!color_policy(None, false));
+        // An empty `NO_COLOR` is ignored
+        assert!(color_policy(Some(OsStr::new("")), true));
+    }
+}
@@ -0,0 +1,61 @@
+//! Known-good answers of every day, checked all at once to catch regressions. The puzzle inputs are
+//! not committed, so the answers are the ones of the committed examples
+
+use anyhow::anyhow;
+
+use crate::{day::Part, DAYS};
+
+/// The expected answer of a part of a day, along with the input it is solved from
+const EXPECTED: &[(usize, Part, &str, &str)] = &[
+    (1, Part::One, "src/day1/example_part1.txt", "142"),
+    (1, Part::Two, "src/day1/example.txt", "281"),
+    (2, Part::One, "src/day2/example.txt", "8"),
+    (2, Part::Two, "src/day2/example.txt", "2286"),
+    (3, Part::One, "src/day3/example.txt", "4361"),
+    (3, Part::Two, "src/day3/example.txt", "467835"),
+    (4, Part::One, "src/day4/example.txt", "13"),
+    (4, Part::Two, "src/day4/example.txt", "30"),
+    (5, Part::One, "src/day5/example.txt", "35"),
+    (5, Part::Two, "src/day5/example.txt", "46"),
+    (6, Part::One, "src/day6/example.txt", "288"),
+    (6, Part::Two, "src/day6/example.txt", "71503"),
+    (7, Part::One, "src/day7/example.txt", "6440"),
+    (7, Part::Two, "src/day7/example.txt", "5905"),
+];
+
+#[test]
+fn every_day_is_verified() {
+    for (day, _, _, _) in DAYS {
+        for part in [Part::One, Part::Two] {
+            assert!(
+                EXPECTED.iter().any(|(d, p, _, _)| d == day && *p == part),
+                "no expected answer for day {day} {part:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn answers_match_known_values() -> anyhow::Result<()> {
+    for &(day, part, path, expected) in EXPECTED {
+        let (_, _, _, solve) = DAYS
+            .iter()
+            .find(|(d, _, _, _)| *d == day)
+            .ok_or(anyhow!("day {day} is not wired"))?;
+
+        let input = std::fs::read_to_string(path)?;
+        let answers = solve(&input, part)?;
+        let answer = match part {
+            Part::One => answers.part1,
+            Part::Two => answers.part2,
+            Part::All => unreachable!("every part is checked on its own"),
+        };
+        assert_eq!(
+            answer.as_deref(),
+            Some(expected),
+            "day {day} {part:?} on {path}"
+        );
+    }
+
+    Ok(())
+}